@@ -11,7 +11,10 @@ use smol::{channel::Sender, lock::Mutex};
 use torque_tracker_engine::{
     audio_processing::playback::PlaybackStatus,
     manager::{AudioManager, OutputConfig, PlaybackSettings, ToWorkerMsg},
-    project::song::{Song, SongOperation},
+    project::{
+        note_event::NoteEvent,
+        song::{Song, SongOperation},
+    },
 };
 use triple_buffer::triple_buffer;
 use winit::{
@@ -28,6 +31,7 @@ use cpal::{
 };
 
 use crate::{
+    history::{History, SongMsg},
     palettes::Palette,
     ui::pages::{order_list::OrderListPageEvent, pattern::PatternPageEvent},
 };
@@ -51,11 +55,20 @@ pub static EXECUTOR: smol::Executor = smol::Executor::new();
 pub static SONG_MANAGER: LazyLock<smol::lock::Mutex<AudioManager>> =
     LazyLock::new(|| Mutex::new(AudioManager::new(Song::default())));
 /// Sender for Song changes
-pub static SONG_OP_SEND: OnceLock<smol::channel::Sender<SongOperation>> = OnceLock::new();
+pub static SONG_OP_SEND: OnceLock<smol::channel::Sender<SongMsg>> = OnceLock::new();
+
+pub fn send_song_msg(msg: SongMsg) {
+    SONG_OP_SEND.get().unwrap().send_blocking(msg).unwrap();
+}
 
 /// shorter function name
 pub fn send_song_op(op: SongOperation) {
-    SONG_OP_SEND.get().unwrap().send_blocking(op).unwrap();
+    send_song_msg(SongMsg::Op(op));
+}
+
+/// the operations are applied together and are undone in one step
+pub fn send_song_ops(ops: impl IntoIterator<Item = SongOperation>) {
+    send_song_msg(SongMsg::Batch(ops.into_iter().collect()));
 }
 
 pub enum GlobalEvent {
@@ -111,6 +124,9 @@ pub enum PlaybackType {
     Pattern,
     FromOrder,
     FromCursor,
+    /// preview of a single note. Plays until it's stopped
+    LiveNote(NoteEvent),
+    StopLiveNote,
 }
 
 struct WorkerThreads {
@@ -190,9 +206,12 @@ impl ApplicationHandler<GlobalEvent> for App {
             self.worker_threads = Some(WorkerThreads::new());
             let (send, recv) = smol::channel::unbounded();
             SONG_OP_SEND.get_or_init(|| send);
+            let proxy = self.event_loop_proxy.clone();
             EXECUTOR
                 .spawn(async move {
-                    while let Ok(op) = recv.recv().await {
+                    // only accessed from this task, so it doesn't need to be locked
+                    let mut history = History::default();
+                    while let Ok(msg) = recv.recv().await {
                         let mut manager = SONG_MANAGER.lock().await;
                         // if there is no active channel the buffer isn't used, so it doesn't matter that it's wrong
                         let buffer_time = manager.last_buffer_time();
@@ -204,14 +223,18 @@ impl ApplicationHandler<GlobalEvent> for App {
                             // smol mutex lock is held across await point
                             smol::Timer::after(buffer_time).await;
                         };
-                        // apply the received op
-                        song.apply_operation(op).unwrap();
+                        // apply the received message
+                        let mut ui_events = history.process(&mut song, msg);
                         // try to get more ops. This avoids repeated locking of the song when a lot of operations are
                         // in queue
-                        while let Ok(op) = recv.try_recv() {
-                            song.apply_operation(op).unwrap();
+                        while let Ok(msg) = recv.try_recv() {
+                            ui_events.append(&mut history.process(&mut song, msg));
                         }
                         drop(song);
+                        drop(manager);
+                        for event in ui_events {
+                            proxy.send_event(event).unwrap();
+                        }
                     }
                 })
                 .detach();
@@ -363,6 +386,7 @@ impl ApplicationHandler<GlobalEvent> for App {
         let event_queue = &mut EventQueue(&mut self.event_queue);
         match event {
             GlobalEvent::OpenDialog(dialog) => {
+                self.ui_pages.stop_live_notes(event_queue);
                 self.dialog_manager.open_dialog(dialog());
                 _ = self.try_request_redraw();
             }
@@ -376,7 +400,7 @@ impl ApplicationHandler<GlobalEvent> for App {
             }
             GlobalEvent::GoToPage(pages_enum) => {
                 self.dialog_manager.close_all();
-                self.ui_pages.switch_page(pages_enum);
+                self.ui_pages.switch_page(pages_enum, event_queue);
                 _ = self.try_request_redraw();
             }
             GlobalEvent::CloseApp => event_loop.exit(),
@@ -398,7 +422,11 @@ impl ApplicationHandler<GlobalEvent> for App {
                     PlaybackType::FromOrder => {
                         Some(ToWorkerMsg::Playback(self.header.play_current_order()))
                     }
+                    // TODO: the engine always starts at the beginning of the pattern, so it can't start at
+                    // the cursor row yet
                     PlaybackType::FromCursor => None,
+                    PlaybackType::LiveNote(event) => Some(ToWorkerMsg::PlayEvent(event)),
+                    PlaybackType::StopLiveNote => Some(ToWorkerMsg::StopLiveNote),
                 };
 
                 if let Some(msg) = msg {
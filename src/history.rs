@@ -0,0 +1,421 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use torque_tracker_engine::{
+    manager::SongEdit,
+    project::{
+        pattern::PatternOperation,
+        song::{Song, SongOperation},
+    },
+    sample::SampleMetaData,
+};
+
+use crate::{
+    app::GlobalEvent,
+    ui::pages::{
+        PageEvent, SDCChange, SampleListEvent, order_list::OrderListPageEvent,
+        pattern::PatternPageEvent,
+    },
+};
+
+/// Message to the task that edits the song
+#[derive(Debug)]
+pub enum SongMsg {
+    Op(SongOperation),
+    /// is undone in one step
+    Batch(Vec<SongOperation>),
+    /// new metadata for the sample in the slot. Unlike other sample changes, quick edits of the same slot are
+    /// merged like the ones of a single value
+    SampleMeta(u8, SampleMetaData),
+    Undo,
+    Redo,
+    /// isn't an edit, so it isn't in the history. The engine can't mute channels, so muted channels get
+    /// volume 0 in the song
+    SetMuted(u8, bool),
+}
+
+/// Undo and redo stacks. Every step stores the operations that restore the song to before the step.
+#[derive(Debug)]
+pub struct History {
+    undo: VecDeque<Vec<SongOperation>>,
+    redo: Vec<Vec<SongOperation>>,
+    /// when the newest undo step was last extended. Used to merge quick edits of the same value
+    last_edit: Option<Instant>,
+    /// sample slot if the newest undo step only changed its metadata
+    meta_edit: Option<u8>,
+    /// volume of the muted channels. Edits of it are kept here until the channel is unmuted
+    muted_volume: [Option<u8>; Song::MAX_CHANNELS],
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            last_edit: None,
+            meta_edit: None,
+            muted_volume: [None; Song::MAX_CHANNELS],
+        }
+    }
+}
+
+impl History {
+    /// oldest steps are forgotten when there are more
+    const MAX_STEPS: usize = 256;
+    /// single edits of the same value closer together than this are undone in one step. Moving a slider
+    /// sends an edit for every tick
+    const MERGE_TIME: Duration = Duration::from_secs(1);
+
+    /// Applies the message to the song. Returns the events needed to show the changes of undo and redo in the UI.
+    /// Normal edits come from the UI, so they don't create events.
+    pub fn process(&mut self, song: &mut SongEdit<'_>, msg: SongMsg) -> Vec<GlobalEvent> {
+        match msg {
+            SongMsg::Op(op) => {
+                let merge = self
+                    .undo
+                    .back()
+                    .is_some_and(|step| matches!(step.as_slice(), [last] if same_value(last, &op)));
+                self.single_edit(song, op, merge, None)
+            }
+            SongMsg::SampleMeta(idx, meta) => {
+                let Some(Some((_, sample))) = song.song().samples.get(usize::from(idx)) else {
+                    return Vec::new();
+                };
+                let op = SongOperation::SetSample(idx, meta, sample.clone());
+                let merge = self.meta_edit == Some(idx);
+                self.single_edit(song, op, merge, Some(idx))
+            }
+            SongMsg::Batch(ops) => {
+                let (inverse, _) = self.apply(song, ops);
+                // don't fill the history with empty steps
+                if inverse.is_empty() {
+                    return Vec::new();
+                }
+                self.push_undo(inverse);
+                self.redo.clear();
+                self.last_edit = None;
+                Vec::new()
+            }
+            SongMsg::Undo => {
+                let Some(ops) = self.undo.pop_back() else {
+                    return Vec::new();
+                };
+                let (inverse, events) = self.apply(song, ops);
+                if !inverse.is_empty() {
+                    self.redo.push(inverse);
+                }
+                self.last_edit = None;
+                events
+            }
+            SongMsg::Redo => {
+                let Some(ops) = self.redo.pop() else {
+                    return Vec::new();
+                };
+                let (inverse, events) = self.apply(song, ops);
+                if !inverse.is_empty() {
+                    self.push_undo(inverse);
+                }
+                self.last_edit = None;
+                events
+            }
+            SongMsg::SetMuted(channel, mute) => {
+                let Some(muted_volume) = self.muted_volume.get_mut(usize::from(channel)) else {
+                    return Vec::new();
+                };
+                let volume = match (mute, *muted_volume) {
+                    (true, None) => {
+                        *muted_volume = Some(song.song().volume[usize::from(channel)]);
+                        0
+                    }
+                    (false, Some(volume)) => {
+                        *muted_volume = None;
+                        volume
+                    }
+                    _ => return Vec::new(),
+                };
+                song.apply_operation(SongOperation::SetVolume(channel, volume))
+                    .expect("the channel exists");
+                Vec::new()
+            }
+        }
+    }
+
+    /// merge is only done if the newest undo step was extended recently. The step already restores the value
+    /// from before the first edit, so nothing is added to it
+    fn single_edit(
+        &mut self,
+        song: &mut SongEdit<'_>,
+        op: SongOperation,
+        merge: bool,
+        meta_edit: Option<u8>,
+    ) -> Vec<GlobalEvent> {
+        let merge = merge
+            && self
+                .last_edit
+                .is_some_and(|t| t.elapsed() < Self::MERGE_TIME);
+        let (inverse, _) = self.apply(song, vec![op]);
+        if inverse.is_empty() {
+            return Vec::new();
+        }
+        if !merge {
+            self.push_undo(inverse);
+        }
+        self.redo.clear();
+        self.last_edit = Some(Instant::now());
+        self.meta_edit = meta_edit;
+        Vec::new()
+    }
+
+    /// applies the operations in order and returns the operations that revert them and the events that show
+    /// the changes in the UI. Operations the song rejects are skipped, so they don't end up in the history
+    fn apply(
+        &mut self,
+        song: &mut SongEdit<'_>,
+        ops: Vec<SongOperation>,
+    ) -> (Vec<SongOperation>, Vec<GlobalEvent>) {
+        let mut inverse = Vec::with_capacity(ops.len());
+        let mut events = Vec::with_capacity(ops.len());
+        for op in ops {
+            // muted channels keep volume 0 in the song
+            if let SongOperation::SetVolume(channel, volume) = op
+                && let Some(Some(muted_volume)) = self.muted_volume.get_mut(usize::from(channel))
+            {
+                inverse.push(SongOperation::SetVolume(channel, *muted_volume));
+                events.push(ui_event(&op));
+                *muted_volume = volume;
+                continue;
+            }
+            // has to be computed before every operation, because multiple operations can change the same value
+            let inverse_op = inverse_op(song.song(), &op);
+            let event = ui_event(&op);
+            if song.apply_operation(op).is_ok() {
+                inverse.push(inverse_op);
+                events.push(event);
+            }
+        }
+        // revert in the opposite order
+        inverse.reverse();
+        (inverse, events)
+    }
+
+    fn push_undo(&mut self, step: Vec<SongOperation>) {
+        if self.undo.len() == Self::MAX_STEPS {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(step);
+    }
+}
+
+/// true if both operations set the same value. Pattern and sample edits are never merged, each of them
+/// can replace a lot of data
+fn same_value(a: &SongOperation, b: &SongOperation) -> bool {
+    match (a, b) {
+        (SongOperation::SetVolume(a, _), SongOperation::SetVolume(b, _))
+        | (SongOperation::SetPan(a, _), SongOperation::SetPan(b, _)) => a == b,
+        (SongOperation::SetInitialSpeed(_), SongOperation::SetInitialSpeed(_))
+        | (SongOperation::SetInitialTempo(_), SongOperation::SetInitialTempo(_))
+        | (SongOperation::SetGlobalVol(_), SongOperation::SetGlobalVol(_)) => true,
+        _ => false,
+    }
+}
+
+/// operation that restores the current value of what op changes
+fn inverse_op(song: &Song, op: &SongOperation) -> SongOperation {
+    match op {
+        SongOperation::SetVolume(channel, _) => {
+            SongOperation::SetVolume(*channel, song.volume[usize::from(*channel)])
+        }
+        SongOperation::SetPan(channel, _) => {
+            SongOperation::SetPan(*channel, song.pan[usize::from(*channel)])
+        }
+        SongOperation::SetSample(idx, _, _) | SongOperation::RemoveSample(idx) => {
+            match &song.samples[usize::from(*idx)] {
+                Some((meta, sample)) => SongOperation::SetSample(*idx, *meta, sample.clone()),
+                None => SongOperation::RemoveSample(*idx),
+            }
+        }
+        SongOperation::PatternOperation(idx, pattern_op) => {
+            let pattern = &song.patterns[usize::from(*idx)];
+            let inverse = match pattern_op {
+                PatternOperation::SetEvent { position, .. }
+                | PatternOperation::RemoveEvent { position } => {
+                    match pattern.get_event(*position) {
+                        Some(event) => PatternOperation::SetEvent {
+                            position: *position,
+                            event: *event,
+                        },
+                        None => PatternOperation::RemoveEvent {
+                            position: *position,
+                        },
+                    }
+                }
+                PatternOperation::SetLength { .. } => PatternOperation::SetLength {
+                    new_len: pattern.row_count(),
+                },
+            };
+            SongOperation::PatternOperation(*idx, inverse)
+        }
+        SongOperation::SetOrder(idx, _) => {
+            SongOperation::SetOrder(*idx, song.pattern_order[usize::from(*idx)])
+        }
+        SongOperation::SetInitialSpeed(_) => SongOperation::SetInitialSpeed(song.initial_speed),
+        SongOperation::SetInitialTempo(_) => SongOperation::SetInitialTempo(song.initial_tempo),
+        SongOperation::SetGlobalVol(_) => SongOperation::SetGlobalVol(song.global_volume),
+    }
+}
+
+/// Event that shows the change of the operation in the UI
+fn ui_event(op: &SongOperation) -> GlobalEvent {
+    let event = match op {
+        SongOperation::SetVolume(channel, vol) => {
+            PageEvent::OrderList(OrderListPageEvent::SetVolume(*channel, *vol))
+        }
+        SongOperation::SetPan(channel, pan) => {
+            PageEvent::OrderList(OrderListPageEvent::SetPan(*channel, *pan))
+        }
+        SongOperation::SetSample(idx, meta, sample) => PageEvent::SampleList(
+            SampleListEvent::SampleChanged(*idx, Some((*meta, sample.clone()))),
+        ),
+        SongOperation::RemoveSample(idx) => {
+            PageEvent::SampleList(SampleListEvent::SampleChanged(*idx, None))
+        }
+        SongOperation::PatternOperation(idx, pattern_op) => {
+            PageEvent::Pattern(PatternPageEvent::Changed(*idx, *pattern_op))
+        }
+        SongOperation::SetOrder(idx, order) => {
+            PageEvent::OrderList(OrderListPageEvent::SetOrder(*idx, *order))
+        }
+        SongOperation::SetInitialSpeed(speed) => PageEvent::Sdc(SDCChange::SongSpeed(speed.get())),
+        SongOperation::SetInitialTempo(tempo) => PageEvent::Sdc(SDCChange::SongTempo(tempo.get())),
+        SongOperation::SetGlobalVol(vol) => PageEvent::Sdc(SDCChange::SongGlobalVolume(*vol)),
+    };
+    GlobalEvent::Page(event)
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZero;
+
+    use torque_tracker_engine::{
+        file::impulse_format::sample::VibratoWave,
+        manager::AudioManager,
+        project::{
+            note_event::Note,
+            pattern::{InPatternPosition, Pattern, PatternOperation},
+            song::{Song, SongOperation},
+        },
+        sample::{Sample, SampleMetaData},
+    };
+
+    use super::{History, SongMsg};
+
+    #[test]
+    fn quick_edits_of_one_value_are_one_step() {
+        let mut manager = AudioManager::new(Song::default());
+        let mut song = manager.try_edit_song().unwrap();
+        let mut history = History::default();
+        for vol in [10, 20, 30] {
+            history.process(&mut song, SongMsg::Op(SongOperation::SetVolume(0, vol)));
+        }
+        history.process(&mut song, SongMsg::Op(SongOperation::SetVolume(1, 5)));
+        assert_eq!(history.undo.len(), 2);
+
+        history.process(&mut song, SongMsg::Undo);
+        history.process(&mut song, SongMsg::Undo);
+        assert_eq!(song.song().volume[0], 64);
+        assert_eq!(song.song().volume[1], 64);
+    }
+
+    fn meta(default_volume: u8) -> SampleMetaData {
+        SampleMetaData {
+            default_volume,
+            global_volume: 64,
+            default_pan: None,
+            vibrato_speed: 0,
+            vibrato_depth: 0,
+            vibrato_rate: 0,
+            vibrato_waveform: VibratoWave::Sine,
+            sample_rate: NonZero::new(44100).unwrap(),
+            base_note: Note::default(),
+        }
+    }
+
+    #[test]
+    fn sample_changes_are_separate_steps() {
+        let mut manager = AudioManager::new(Song::default());
+        let mut song = manager.try_edit_song().unwrap();
+        let mut history = History::default();
+        for len in [4, 8] {
+            let sample = Sample::new_mono(vec![0.; len]);
+            history.process(
+                &mut song,
+                SongMsg::Op(SongOperation::SetSample(0, meta(64), sample)),
+            );
+        }
+        assert_eq!(history.undo.len(), 2);
+    }
+
+    #[test]
+    fn quick_meta_edits_of_one_sample_are_one_step() {
+        let mut manager = AudioManager::new(Song::default());
+        let mut song = manager.try_edit_song().unwrap();
+        let mut history = History::default();
+        let sample = Sample::new_mono(vec![0.; 4]);
+        history.process(
+            &mut song,
+            SongMsg::Op(SongOperation::SetSample(0, meta(64), sample)),
+        );
+        for vol in [10, 20, 30] {
+            history.process(&mut song, SongMsg::SampleMeta(0, meta(vol)));
+        }
+        assert_eq!(history.undo.len(), 2);
+
+        history.process(&mut song, SongMsg::Undo);
+        let (undone, _) = song.song().samples[0].as_ref().unwrap();
+        assert_eq!(undone.default_volume, 64);
+        // empty slots have no metadata to change
+        history.process(&mut song, SongMsg::SampleMeta(1, meta(10)));
+        assert_eq!(history.undo.len(), 1);
+    }
+
+    #[test]
+    fn rejected_operations_are_not_recorded() {
+        let mut manager = AudioManager::new(Song::default());
+        let mut song = manager.try_edit_song().unwrap();
+        let mut history = History::default();
+        let op = SongOperation::PatternOperation(
+            0,
+            PatternOperation::SetEvent {
+                // past the end of the pattern
+                position: InPatternPosition {
+                    row: Pattern::MAX_ROWS,
+                    channel: 0,
+                },
+                event: Default::default(),
+            },
+        );
+        history.process(&mut song, SongMsg::Op(op.clone()));
+        history.process(&mut song, SongMsg::Batch(vec![op]));
+        assert!(history.undo.is_empty());
+    }
+
+    #[test]
+    fn muted_channels_keep_their_volume() {
+        let mut manager = AudioManager::new(Song::default());
+        let mut song = manager.try_edit_song().unwrap();
+        let mut history = History::default();
+        history.process(&mut song, SongMsg::SetMuted(0, true));
+        assert_eq!(song.song().volume[0], 0);
+        assert!(history.undo.is_empty());
+
+        history.process(&mut song, SongMsg::Op(SongOperation::SetVolume(0, 20)));
+        assert_eq!(song.song().volume[0], 0);
+        history.process(&mut song, SongMsg::SetMuted(0, false));
+        assert_eq!(song.song().volume[0], 20);
+
+        history.process(&mut song, SongMsg::Undo);
+        assert_eq!(song.song().volume[0], 64);
+    }
+}
@@ -3,6 +3,7 @@ pub mod coordinates;
 pub mod draw_buffer;
 #[cfg(feature = "gpu_scaling")]
 pub mod gpu;
+pub mod history;
 pub mod palettes;
 pub mod render;
 pub mod ui;
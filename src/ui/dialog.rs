@@ -1,5 +1,7 @@
 pub mod confirm;
+pub mod error;
 pub mod page_menu;
+pub mod pattern_properties;
 pub mod slider_dialog;
 
 use winit::event::{KeyEvent, Modifiers};
@@ -0,0 +1,68 @@
+use winit::keyboard::{Key, NamedKey};
+
+use crate::{
+    coordinates::{CharPosition, CharRect, WINDOW_SIZE_CHARS},
+    draw_buffer::DrawBuffer,
+    ui::widgets::{NextWidget, StandardResponse, Widget, WidgetResponse, button::Button},
+};
+
+use super::{Dialog, DialogResponse};
+
+/// shows a message with an Ok button
+pub struct ErrorDialog {
+    text: String,
+    text_pos: CharPosition,
+    // computed from the string length
+    rect: CharRect,
+    ok: Button<()>,
+}
+
+impl ErrorDialog {
+    const OK_RECT: CharRect = CharRect::new(29, 31, 35, 44);
+    /// longer texts are cut off
+    const MAX_TEXT_LEN: usize = WINDOW_SIZE_CHARS.0 - 14;
+
+    pub fn new(mut text: String) -> Self {
+        if let Some((end, _)) = text.char_indices().nth(Self::MAX_TEXT_LEN) {
+            text.truncate(end);
+        }
+        let width = (text.chars().count() + 10).max(22);
+        let per_side = width / 2;
+        Self {
+            text,
+            text_pos: CharPosition::new(40 - per_side + 5, 27),
+            rect: CharRect::new(25, 32, 40 - per_side, 40 + per_side),
+            ok: Button::new("  Ok", Self::OK_RECT, NextWidget::default(), || ()),
+        }
+    }
+}
+
+impl Dialog for ErrorDialog {
+    fn draw(&self, draw_buffer: &mut DrawBuffer) {
+        draw_buffer.draw_rect(2, self.rect);
+        draw_buffer.draw_out_border(self.rect, 3, 3, 2);
+        draw_buffer.draw_string(&self.text, self.text_pos, 0, 2);
+        self.ok.draw(draw_buffer, true);
+    }
+
+    fn process_input(
+        &mut self,
+        key_event: &winit::event::KeyEvent,
+        modifiers: &winit::event::Modifiers,
+        events: &mut crate::app::EventQueue<'_>,
+    ) -> DialogResponse {
+        if key_event.logical_key == Key::Named(NamedKey::Escape) && modifiers.state().is_empty() {
+            return DialogResponse::Close;
+        }
+
+        let WidgetResponse { standard, extra } =
+            self.ok.process_input(modifiers, key_event, events);
+        if extra.is_some() {
+            return DialogResponse::Close;
+        }
+        match standard {
+            StandardResponse::RequestRedraw => DialogResponse::RequestRedraw,
+            StandardResponse::SwitchFocus(_) | StandardResponse::None => DialogResponse::None,
+        }
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
     app::{EventQueue, GlobalEvent, PlaybackType},
     coordinates::{CharPosition, CharRect, FONT_SIZE, PixelRect},
     draw_buffer::DrawBuffer,
-    ui::pages::PagesEnum,
+    ui::pages::{PageEvent, PagesEnum, pattern::PatternPageEvent},
 };
 
 use super::{Dialog, DialogResponse};
@@ -291,6 +291,10 @@ impl PageMenu {
                     "Sample List          (F3)",
                     Action::Page(PagesEnum::SampleList),
                 ),
+                (
+                    "Sample Editor  (Shift-F3)",
+                    Action::Page(PagesEnum::SampleEditor),
+                ),
                 ("Sample Library  (Ctrl-F3)", Action::NotYetImplemented),
             ],
         )
@@ -311,7 +315,7 @@ impl PageMenu {
     pub const fn settings() -> Self {
         Self::new(
             "Settings Menu",
-            CharPosition::new(22, 25),
+            CharPosition::new(22, 22),
             38,
             &[
                 (
@@ -338,6 +342,12 @@ impl PageMenu {
                     "Toggle Fullscreen (Ctrl-Alt-Enter)",
                     Action::NotYetImplemented,
                 ),
+                (
+                    "Physical/Logical Note Keys",
+                    Action::Event(GlobalEvent::Page(PageEvent::Pattern(
+                        PatternPageEvent::ToggleNoteKeyLayout,
+                    ))),
+                ),
             ],
         )
     }
@@ -0,0 +1,290 @@
+use torque_tracker_engine::project::pattern::Pattern;
+use winit::keyboard::{Key, NamedKey};
+
+use crate::{
+    app::GlobalEvent,
+    coordinates::{CharPosition, CharRect},
+    draw_buffer::DrawBuffer,
+    ui::{
+        pages::{
+            PageEvent, create_widget_list,
+            pattern::{PatternPageEvent, RowHighlight},
+        },
+        widgets::{
+            NextWidget, StandardResponse, WidgetResponse, button::Button, text_in::TextIn,
+            toggle::Toggle,
+        },
+    },
+};
+
+use super::{Dialog, DialogResponse};
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Ok,
+    Cancel,
+}
+
+create_widget_list!(
+    response: Option<Action>;
+    WidgetList
+    {
+        rows: TextIn<Option<Action>>,
+        beat: TextIn<Option<Action>>,
+        measure: TextIn<Option<Action>>,
+        custom_highlight: Toggle<bool, Option<Action>>,
+        default_beat: TextIn<Option<Action>>,
+        default_measure: TextIn<Option<Action>>,
+        ok: Button<Option<Action>>,
+        cancel: Button<Option<Action>>
+    }
+);
+
+/// Ctrl-F2 on the pattern page. Edits the length and row highlight of the pattern and the default
+/// highlight
+pub struct PatternPropertiesDialog {
+    widgets: WidgetList,
+    /// values when the dialog was opened. Only changed values are sent
+    rows: u16,
+    highlights: (Option<RowHighlight>, RowHighlight),
+    /// shown after Ok was pressed with an invalid number
+    error: Option<String>,
+}
+
+impl PatternPropertiesDialog {
+    const RECT: CharRect = CharRect::new(17, 34, 18, 61);
+    const OK_RECT: CharRect = CharRect::new(30, 32, 41, 50);
+    const CANCEL_RECT: CharRect = CharRect::new(30, 32, 30, 39);
+    const TEXT_X: usize = 42;
+    const NUMBER_WIDTH: usize = 3;
+
+    /// pattern is None if the pattern uses the song default
+    pub fn new(rows: u16, pattern: Option<RowHighlight>, song: RowHighlight) -> Self {
+        fn number_in(row: usize, next_widget: NextWidget, value: u16) -> TextIn<Option<Action>> {
+            let mut text_in = TextIn::new(
+                CharPosition::new(PatternPropertiesDialog::TEXT_X, row),
+                PatternPropertiesDialog::NUMBER_WIDTH,
+                next_widget,
+                |_| None,
+            );
+            text_in.set_string(value.to_string()).unwrap();
+            text_in
+        }
+
+        let shown = pattern.unwrap_or(song);
+        let mut custom_highlight = Toggle::new(
+            CharPosition::new(Self::TEXT_X, 24),
+            12,
+            NextWidget {
+                up: Some(WidgetList::MEASURE),
+                shift_tab: Some(WidgetList::MEASURE),
+                down: Some(WidgetList::DEFAULT_BEAT),
+                tab: Some(WidgetList::DEFAULT_BEAT),
+                ..Default::default()
+            },
+            &[(false, "Song Default"), (true, "Pattern")],
+            |_| None,
+        );
+        if pattern.is_some() {
+            custom_highlight.next();
+        }
+
+        Self {
+            rows,
+            highlights: (pattern, song),
+            error: None,
+            widgets: WidgetList {
+                selected: WidgetList::ROWS,
+                rows: number_in(
+                    21,
+                    NextWidget {
+                        up: Some(WidgetList::CANCEL),
+                        shift_tab: Some(WidgetList::CANCEL),
+                        down: Some(WidgetList::BEAT),
+                        tab: Some(WidgetList::BEAT),
+                        ..Default::default()
+                    },
+                    rows,
+                ),
+                beat: number_in(
+                    22,
+                    NextWidget {
+                        up: Some(WidgetList::ROWS),
+                        shift_tab: Some(WidgetList::ROWS),
+                        down: Some(WidgetList::MEASURE),
+                        tab: Some(WidgetList::MEASURE),
+                        ..Default::default()
+                    },
+                    shown.beat,
+                ),
+                measure: number_in(
+                    23,
+                    NextWidget {
+                        up: Some(WidgetList::BEAT),
+                        shift_tab: Some(WidgetList::BEAT),
+                        down: Some(WidgetList::CUSTOM_HIGHLIGHT),
+                        tab: Some(WidgetList::CUSTOM_HIGHLIGHT),
+                        ..Default::default()
+                    },
+                    shown.measure,
+                ),
+                custom_highlight,
+                default_beat: number_in(
+                    27,
+                    NextWidget {
+                        up: Some(WidgetList::CUSTOM_HIGHLIGHT),
+                        shift_tab: Some(WidgetList::CUSTOM_HIGHLIGHT),
+                        down: Some(WidgetList::DEFAULT_MEASURE),
+                        tab: Some(WidgetList::DEFAULT_MEASURE),
+                        ..Default::default()
+                    },
+                    song.beat,
+                ),
+                default_measure: number_in(
+                    28,
+                    NextWidget {
+                        up: Some(WidgetList::DEFAULT_BEAT),
+                        shift_tab: Some(WidgetList::DEFAULT_BEAT),
+                        down: Some(WidgetList::OK),
+                        tab: Some(WidgetList::OK),
+                        ..Default::default()
+                    },
+                    song.measure,
+                ),
+                ok: Button::new(
+                    "  Ok",
+                    Self::OK_RECT,
+                    NextWidget {
+                        left: Some(WidgetList::CANCEL),
+                        right: Some(WidgetList::CANCEL),
+                        up: Some(WidgetList::DEFAULT_MEASURE),
+                        shift_tab: Some(WidgetList::DEFAULT_MEASURE),
+                        tab: Some(WidgetList::CANCEL),
+                        ..Default::default()
+                    },
+                    || Some(Action::Ok),
+                ),
+                cancel: Button::new(
+                    "Cancel",
+                    Self::CANCEL_RECT,
+                    NextWidget {
+                        left: Some(WidgetList::OK),
+                        right: Some(WidgetList::OK),
+                        up: Some(WidgetList::DEFAULT_MEASURE),
+                        shift_tab: Some(WidgetList::OK),
+                        tab: Some(WidgetList::ROWS),
+                        ..Default::default()
+                    },
+                    || Some(Action::Cancel),
+                ),
+            },
+        }
+    }
+
+    /// highest length the engine accepts
+    const MAX_ROWS: u16 = Pattern::MAX_ROWS - 1;
+
+    /// None if one of the numbers isn't valid
+    fn get_rows(&self) -> Option<u16> {
+        parse(&self.widgets.rows).filter(|rows| *rows <= Self::MAX_ROWS)
+    }
+
+    /// None if one of the numbers isn't valid
+    fn get_highlights(&self) -> Option<(Option<RowHighlight>, RowHighlight)> {
+        let pattern = RowHighlight {
+            beat: parse(&self.widgets.beat)?,
+            measure: parse(&self.widgets.measure)?,
+        };
+        let song = RowHighlight {
+            beat: parse(&self.widgets.default_beat)?,
+            measure: parse(&self.widgets.default_measure)?,
+        };
+        let pattern = self
+            .widgets
+            .custom_highlight
+            .get_variant()
+            .then_some(pattern);
+        Some((pattern, song))
+    }
+}
+
+/// only positive numbers are valid
+fn parse(text_in: &TextIn<Option<Action>>) -> Option<u16> {
+    text_in.get_str().parse().ok().filter(|n| *n > 0)
+}
+
+impl Dialog for PatternPropertiesDialog {
+    fn draw(&self, draw_buffer: &mut DrawBuffer) {
+        draw_buffer.draw_rect(2, Self::RECT);
+        draw_buffer.draw_out_border(Self::RECT, 3, 3, 2);
+        draw_buffer.draw_string("Pattern Properties", CharPosition::new(31, 19), 3, 2);
+
+        draw_buffer.draw_string("Number of Rows", CharPosition::new(27, 21), 0, 2);
+        draw_buffer.draw_string(
+            &format!("(1-{})", Self::MAX_ROWS),
+            CharPosition::new(Self::TEXT_X + Self::NUMBER_WIDTH + 2, 21),
+            0,
+            2,
+        );
+        draw_buffer.draw_string("Rows per Beat", CharPosition::new(28, 22), 0, 2);
+        draw_buffer.draw_string("Rows per Measure", CharPosition::new(25, 23), 0, 2);
+        draw_buffer.draw_string("Highlight", CharPosition::new(32, 24), 0, 2);
+        draw_buffer.draw_string("Song Default", CharPosition::new(35, 26), 3, 2);
+        draw_buffer.draw_string("Rows per Beat", CharPosition::new(28, 27), 0, 2);
+        draw_buffer.draw_string("Rows per Measure", CharPosition::new(25, 28), 0, 2);
+        if let Some(error) = &self.error {
+            draw_buffer.draw_string(error, CharPosition::new(20, 29), 3, 2);
+        }
+        self.widgets.draw_widgets(draw_buffer);
+    }
+
+    fn process_input(
+        &mut self,
+        key_event: &winit::event::KeyEvent,
+        modifiers: &winit::event::Modifiers,
+        events: &mut crate::app::EventQueue<'_>,
+    ) -> DialogResponse {
+        if key_event.logical_key == Key::Named(NamedKey::Escape) && modifiers.state().is_empty() {
+            return DialogResponse::Close;
+        }
+
+        let WidgetResponse { standard, extra } =
+            self.widgets.process_input(key_event, modifiers, events);
+        match extra.flatten() {
+            Some(Action::Ok) => {
+                // stay open, so the invalid number can be fixed
+                let (Some(rows), Some((pattern, song))) = (self.get_rows(), self.get_highlights())
+                else {
+                    self.error = Some(if self.get_rows().is_none() {
+                        format!("Number of Rows has to be 1 to {}", Self::MAX_ROWS)
+                    } else {
+                        String::from("Rows per Beat and Measure can't be 0")
+                    });
+                    return DialogResponse::RequestRedraw;
+                };
+                if (pattern, song) != self.highlights {
+                    events.push(GlobalEvent::Page(PageEvent::Pattern(
+                        PatternPageEvent::SetRowHighlight(pattern, song),
+                    )));
+                }
+                if rows != self.rows {
+                    events.push(GlobalEvent::Page(PageEvent::Pattern(
+                        PatternPageEvent::SetLength(rows),
+                    )));
+                }
+                return DialogResponse::Close;
+            }
+            Some(Action::Cancel) => return DialogResponse::Close,
+            None => (),
+        }
+
+        match standard {
+            StandardResponse::SwitchFocus(next) => {
+                self.widgets.selected = next;
+                DialogResponse::RequestRedraw
+            }
+            StandardResponse::RequestRedraw => DialogResponse::RequestRedraw,
+            StandardResponse::None => DialogResponse::None,
+        }
+    }
+}
@@ -66,9 +66,26 @@ impl SliderDialog {
         inital_char: char,
         range: RangeInclusive<i16>,
         return_event: fn(i16) -> GlobalEvent,
+    ) -> Self {
+        Self::with_text(inital_char.to_string(), range, return_event)
+    }
+
+    /// starts with the value already entered
+    pub fn with_value(
+        value: i16,
+        range: RangeInclusive<i16>,
+        return_event: fn(i16) -> GlobalEvent,
+    ) -> Self {
+        Self::with_text(value.to_string(), range, return_event)
+    }
+
+    fn with_text(
+        text: String,
+        range: RangeInclusive<i16>,
+        return_event: fn(i16) -> GlobalEvent,
     ) -> Self {
         let mut text_in = TextIn::new(CharPosition::new(45, 26), 3, NextWidget::default(), |_| {});
-        text_in.set_string(inital_char.to_string()).unwrap();
+        text_in.set_string(text).unwrap();
         Self {
             text: text_in,
             return_event,
@@ -9,6 +9,7 @@ use torque_tracker_engine::{
 use crate::{
     coordinates::{CharPosition, CharRect},
     draw_buffer::DrawBuffer,
+    ui::note_keys::NoteKeyLayout,
 };
 
 #[derive(Debug, Clone)]
@@ -23,6 +24,12 @@ pub enum HeaderEvent {
     SetSpeed(usize),
     SetTempo(usize),
     SetPlayback(Option<PlaybackPosition>),
+    SetOctave(u8),
+    SetNoteKeyLayout(NoteKeyLayout),
+    /// recording, quantize
+    SetRecord(bool, bool),
+    SetFollow(bool),
+    SetEditStep(u8),
 }
 
 #[derive(Debug)]
@@ -35,6 +42,15 @@ pub struct Header {
     order_len: u16,
     selected_sample: (u8, Box<str>),
     playback: Option<PlaybackPosition>,
+    /// used for note entry
+    octave: u8,
+    note_layout: NoteKeyLayout,
+    /// recording, quantize
+    record: (bool, bool),
+    /// the pattern view follows the playback
+    follow: bool,
+    /// rows skipped after note entry
+    edit_step: u8,
 }
 
 impl Default for Header {
@@ -48,11 +64,19 @@ impl Default for Header {
             order_len: 0,
             selected_sample: (0, Box::from("")),
             playback: None,
+            octave: Self::DEFAULT_OCTAVE,
+            note_layout: NoteKeyLayout::default(),
+            record: (false, false),
+            follow: false,
+            edit_step: 1,
         }
     }
 }
 
 impl Header {
+    pub const DEFAULT_OCTAVE: u8 = 5;
+    pub const MAX_OCTAVE: u8 = 9;
+
     pub fn play_current_pattern(&self) -> PlaybackSettings {
         PlaybackSettings::Pattern {
             idx: self.pattern,
@@ -83,6 +107,14 @@ impl Header {
             HeaderEvent::SetMaxCursorRow(r) => self.max_row = r,
             HeaderEvent::SetMaxCursorPattern(p) => self.max_pattern = p,
             HeaderEvent::SetPlayback(p) => self.playback = p,
+            HeaderEvent::SetOctave(o) => {
+                assert!(o <= Self::MAX_OCTAVE);
+                self.octave = o
+            }
+            HeaderEvent::SetNoteKeyLayout(l) => self.note_layout = l,
+            HeaderEvent::SetRecord(record, quantize) => self.record = (record, quantize),
+            HeaderEvent::SetFollow(follow) => self.follow = follow,
+            HeaderEvent::SetEditStep(s) => self.edit_step = s,
         }
     }
 
@@ -113,6 +145,42 @@ impl Header {
         let mut curse: std::io::Cursor<&mut [u8]> = std::io::Cursor::new(&mut buf);
         write!(&mut curse, "{:03}", self.order_len).unwrap();
         draw_buffer.draw_string(from_utf8(&buf).unwrap(), CharPosition::new(16, 5), 5, 0);
+        // octave
+        let mut curse: std::io::Cursor<&mut [u8]> = std::io::Cursor::new(&mut buf);
+        write!(&mut curse, "{}", self.octave).unwrap();
+        draw_buffer.draw_string(
+            from_utf8(&buf[..1]).unwrap(),
+            CharPosition::new(50, 5),
+            5,
+            0,
+        );
+        // edit step
+        let mut curse: std::io::Cursor<&mut [u8]> = std::io::Cursor::new(&mut buf);
+        write!(&mut curse, "{:02}", self.edit_step).unwrap();
+        draw_buffer.draw_string(
+            from_utf8(&buf[..2]).unwrap(),
+            CharPosition::new(58, 5),
+            5,
+            0,
+        );
+        // note keys
+        let layout = match self.note_layout {
+            NoteKeyLayout::Physical => "Physical",
+            NoteKeyLayout::Logical => "Logical ",
+        };
+        draw_buffer.draw_string(layout, CharPosition::new(67, 5), 5, 0);
+        // record mode
+        match self.record {
+            (true, false) => draw_buffer.draw_string("Record  ", CharPosition::new(43, 8), 3, 2),
+            (true, true) => draw_buffer.draw_string("Record Q", CharPosition::new(43, 8), 3, 2),
+            (false, _) => draw_buffer.draw_rect(2, CharRect::new(8, 8, 43, 50)),
+        }
+        // follow mode
+        if self.follow {
+            draw_buffer.draw_string("Follow", CharPosition::new(52, 8), 3, 2);
+        } else {
+            draw_buffer.draw_rect(2, CharRect::new(8, 8, 52, 57));
+        }
         // sample
         draw_buffer.draw_string_length(&self.selected_sample.1, CharPosition::new(53, 3), 24, 5, 0);
         let mut curse: std::io::Cursor<&mut [u8]> = std::io::Cursor::new(&mut buf);
@@ -165,6 +233,8 @@ impl Header {
         buffer.draw_string("Row", CharPosition::new(8, 7), 0, 2);
         buffer.draw_string("Speed/Tempo", CharPosition::new(38, 4), 0, 2);
         buffer.draw_string("Octave", CharPosition::new(43, 5), 0, 2);
+        buffer.draw_string("Step", CharPosition::new(53, 5), 0, 2);
+        buffer.draw_string("Keys", CharPosition::new(62, 5), 0, 2);
         buffer.draw_string(
             "F1...Help       F9.....Load",
             CharPosition::new(21, 6),
@@ -1,4 +1,6 @@
 pub mod dialog;
 pub mod header;
+pub mod note_keys;
 pub mod pages;
+pub mod waveform;
 pub mod widgets;
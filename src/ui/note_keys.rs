@@ -0,0 +1,111 @@
+use torque_tracker_engine::project::note_event::Note;
+use winit::{
+    event::KeyEvent,
+    keyboard::{Key, KeyCode, PhysicalKey},
+};
+
+/// highest note that can be entered. B-9
+pub const MAX_NOTE: u8 = 119;
+
+/// Which keys are used as the piano
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NoteKeyLayout {
+    /// always the same place on the keyboard, independent of the keyboard layout.
+    /// Uses the names of the US QWERTY keys.
+    #[default]
+    Physical,
+    /// the characters of the keys as on US QWERTY. Other layouts move the notes around
+    Logical,
+}
+
+impl NoteKeyLayout {
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::Physical => Self::Logical,
+            Self::Logical => Self::Physical,
+        }
+    }
+}
+
+/// key for the physical layout, character of that key on US QWERTY for the logical layout, semitone,
+/// octave relative to the current one
+const NOTE_KEYS: [(KeyCode, &str, u8, i8); 29] = [
+    // lower octave
+    (KeyCode::KeyZ, "z", 0, -1), // C-1
+    (KeyCode::KeyS, "s", 1, -1),
+    (KeyCode::KeyX, "x", 2, -1),
+    (KeyCode::KeyD, "d", 3, -1),
+    (KeyCode::KeyC, "c", 4, -1),
+    (KeyCode::KeyV, "v", 5, -1),
+    (KeyCode::KeyG, "g", 6, -1),
+    (KeyCode::KeyB, "b", 7, -1),
+    (KeyCode::KeyH, "h", 8, -1),
+    (KeyCode::KeyN, "n", 9, -1),
+    (KeyCode::KeyJ, "j", 10, -1),
+    (KeyCode::KeyM, "m", 11, -1),
+    // base octave
+    (KeyCode::KeyQ, "q", 0, 0),    // C
+    (KeyCode::Digit2, "2", 1, 0),  // Db / C#
+    (KeyCode::KeyW, "w", 2, 0),    // D
+    (KeyCode::Digit3, "3", 3, 0),  // Eb / D#
+    (KeyCode::KeyE, "e", 4, 0),    // E
+    (KeyCode::KeyR, "r", 5, 0),    // F
+    (KeyCode::Digit5, "5", 6, 0),  // Gb / F#
+    (KeyCode::KeyT, "t", 7, 0),    // G
+    (KeyCode::Digit6, "6", 8, 0),  // Ab / G#
+    (KeyCode::KeyY, "y", 9, 0),    // A
+    (KeyCode::Digit7, "7", 10, 0), // Bb / A#
+    (KeyCode::KeyU, "u", 11, 0),   // B
+    // higher octave
+    (KeyCode::KeyI, "i", 0, 1), // C+1
+    (KeyCode::Digit9, "9", 1, 1),
+    (KeyCode::KeyO, "o", 2, 1),
+    (KeyCode::Digit0, "0", 3, 1),
+    (KeyCode::KeyP, "p", 4, 1),
+];
+
+/// None if the key isn't a note key or the note would be out of range
+pub fn note_from_key(key_event: &KeyEvent, layout: NoteKeyLayout, octave: u8) -> Option<Note> {
+    let (_, _, semitone, octave_offset) = match layout {
+        NoteKeyLayout::Physical => NOTE_KEYS
+            .iter()
+            .find(|(code, ..)| key_event.physical_key == PhysicalKey::Code(*code))?,
+        NoteKeyLayout::Logical => match &key_event.logical_key {
+            Key::Character(char) => NOTE_KEYS
+                .iter()
+                .find(|(_, key, ..)| char.as_str() == *key)?,
+            _ => return None,
+        },
+    };
+    note_in_octave(*semitone, octave.checked_add_signed(*octave_offset)?)
+}
+
+/// None if the note is out of range
+pub fn note_in_octave(semitone: u8, octave: u8) -> Option<Note> {
+    let note = octave.checked_mul(12)?.checked_add(semitone)?;
+    if note > MAX_NOTE {
+        return None;
+    }
+    Note::new(note).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::NOTE_KEYS;
+
+    #[test]
+    fn note_keys_are_unique() {
+        for (idx, (code, key, semitone, octave)) in NOTE_KEYS.iter().enumerate() {
+            assert!(*semitone < 12);
+            assert!((-1..=1).contains(octave));
+            assert!(
+                NOTE_KEYS[idx + 1..]
+                    .iter()
+                    .all(|(c, k, ..)| c != code && k != key)
+            );
+            // the character has to be the one of the physical key on US QWERTY
+            let name = format!("{code:?}").to_lowercase();
+            assert!(name.ends_with(key), "{name} {key}");
+        }
+    }
+}
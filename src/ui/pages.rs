@@ -1,25 +1,30 @@
 mod help_page;
 pub mod order_list;
 pub mod pattern;
+mod sample_editor;
 mod sample_list;
 mod song_directory_config_page;
 
 use help_page::HelpPage;
 use order_list::{OrderListPage, OrderListPageEvent};
 use pattern::{PatternPage, PatternPageEvent};
+use sample_editor::SampleEditor;
+pub use sample_editor::SampleEditorEvent;
 use sample_list::SampleList;
-use song_directory_config_page::{SDCChange, SongDirectoryConfigPage};
+pub use sample_list::SampleListEvent;
+pub use song_directory_config_page::SDCChange;
+use song_directory_config_page::SongDirectoryConfigPage;
 use winit::{
     event::{KeyEvent, Modifiers},
     event_loop::EventLoopProxy,
-    keyboard::{Key, NamedKey},
+    keyboard::{Key, KeyCode, ModifiersState, NamedKey, PhysicalKey},
 };
 
 use crate::{
-    app::{EventQueue, GlobalEvent},
+    app::{EventQueue, GlobalEvent, send_song_msg},
     coordinates::{CharPosition, CharRect, WINDOW_SIZE_CHARS},
     draw_buffer::DrawBuffer,
-    ui::pages::sample_list::SampleListEvent,
+    history::SongMsg,
 };
 
 pub trait Page {
@@ -122,6 +127,7 @@ pub enum PagesEnum {
     Pattern,
     OrderList,
     SampleList,
+    SampleEditor,
 }
 
 #[derive(Debug, Clone)]
@@ -130,6 +136,7 @@ pub enum PageEvent {
     Pattern(PatternPageEvent),
     OrderList(OrderListPageEvent),
     SampleList(SampleListEvent),
+    SampleEditor(SampleEditorEvent),
 }
 
 impl PageEvent {
@@ -139,6 +146,7 @@ impl PageEvent {
             PageEvent::Pattern(_) => PagesEnum::Pattern,
             PageEvent::OrderList(_) => PagesEnum::OrderList,
             PageEvent::SampleList(_) => PagesEnum::SampleList,
+            PageEvent::SampleEditor(_) => PagesEnum::SampleEditor,
         }
     }
 }
@@ -149,6 +157,7 @@ pub struct AllPages {
     pattern: PatternPage,
     order_list: OrderListPage,
     sample_list: SampleList,
+    sample_editor: SampleEditor,
 
     const_draw_needed: bool,
     current: PagesEnum,
@@ -164,6 +173,7 @@ impl AllPages {
             order_list: OrderListPage::new(),
             const_draw_needed: true,
             sample_list: SampleList::new(proxy),
+            sample_editor: SampleEditor::new(),
         }
     }
 
@@ -177,6 +187,7 @@ impl AllPages {
                 order_list::Mode::Panning => "Order List and Panning (F11)",
             },
             PagesEnum::SampleList => "Sample List (F3)",
+            PagesEnum::SampleEditor => "Sample Editor (Shift-F3)",
         }
     }
 
@@ -187,6 +198,7 @@ impl AllPages {
             PagesEnum::Pattern => &self.pattern,
             PagesEnum::OrderList => &self.order_list,
             PagesEnum::SampleList => &self.sample_list,
+            PagesEnum::SampleEditor => &self.sample_editor,
         }
     }
 
@@ -197,12 +209,18 @@ impl AllPages {
             PagesEnum::Pattern => &mut self.pattern,
             PagesEnum::OrderList => &mut self.order_list,
             PagesEnum::SampleList => &mut self.sample_list,
+            PagesEnum::SampleEditor => &mut self.sample_editor,
         }
     }
 
     /// requests a redraw if it is needed
-    pub fn switch_page(&mut self, next_page: PagesEnum) -> PageResponse {
+    pub fn switch_page(
+        &mut self,
+        next_page: PagesEnum,
+        events: &mut EventQueue<'_>,
+    ) -> PageResponse {
         if next_page != self.current {
+            self.stop_live_notes(events);
             // when switching to the OrderListPage, reset to the pan mode
             if next_page == PagesEnum::OrderList {
                 self.order_list.reset_mode();
@@ -215,6 +233,12 @@ impl AllPages {
         }
     }
 
+    /// the key release of a playing note goes to the new page or dialog, so the note has to be stopped here
+    pub fn stop_live_notes(&mut self, events: &mut EventQueue<'_>) {
+        self.pattern.stop_live_note(events);
+        self.sample_list.stop_live_note(events);
+    }
+
     pub fn request_draw_const(&mut self) {
         self.const_draw_needed = true;
     }
@@ -258,10 +282,10 @@ impl AllPages {
     ) -> PageResponse {
         if key_event.state.is_pressed() && modifiers.state().is_empty() {
             if key_event.logical_key == Key::Named(NamedKey::F1) {
-                self.switch_page(PagesEnum::Help);
+                self.switch_page(PagesEnum::Help, events);
                 return PageResponse::RequestRedraw;
             } else if key_event.logical_key == Key::Named(NamedKey::F2) {
-                self.switch_page(PagesEnum::Pattern);
+                self.switch_page(PagesEnum::Pattern, events);
                 return PageResponse::RequestRedraw;
             } else if key_event.logical_key == Key::Named(NamedKey::F11) {
                 if self.current == PagesEnum::OrderList {
@@ -269,18 +293,50 @@ impl AllPages {
                     self.request_draw_const();
                     return PageResponse::RequestRedraw;
                 } else {
-                    self.switch_page(PagesEnum::OrderList);
+                    self.switch_page(PagesEnum::OrderList, events);
                     return PageResponse::RequestRedraw;
                 }
             } else if key_event.logical_key == Key::Named(NamedKey::F12) {
-                self.switch_page(PagesEnum::SongDirectoryConfig);
+                self.switch_page(PagesEnum::SongDirectoryConfig, events);
                 return PageResponse::RequestRedraw;
             } else if key_event.logical_key == Key::Named(NamedKey::F3) {
-                self.switch_page(PagesEnum::SampleList);
+                self.switch_page(PagesEnum::SampleList, events);
                 return PageResponse::RequestRedraw;
             }
         }
 
+        if key_event.state.is_pressed()
+            && modifiers.state() == ModifiersState::SHIFT
+            && key_event.logical_key == Key::Named(NamedKey::F3)
+        {
+            self.switch_page(PagesEnum::SampleEditor, events);
+            return PageResponse::RequestRedraw;
+        }
+
+        // the octave is used by the pattern editor and the sample list, so it can be changed on every page
+        if key_event.state.is_pressed()
+            && let PhysicalKey::Code(code @ (KeyCode::NumpadDivide | KeyCode::NumpadMultiply)) =
+                key_event.physical_key
+        {
+            self.pattern
+                .change_octave(code == KeyCode::NumpadMultiply, events);
+            return PageResponse::None;
+        }
+
+        // undo and redo work on every page. The pages get the changes as page events
+        if key_event.state.is_pressed()
+            && modifiers.state() == ModifiersState::CONTROL
+            && let Key::Character(char) = &key_event.logical_key
+        {
+            if char.eq_ignore_ascii_case("z") {
+                send_song_msg(SongMsg::Undo);
+                return PageResponse::None;
+            } else if char.eq_ignore_ascii_case("y") {
+                send_song_msg(SongMsg::Redo);
+                return PageResponse::None;
+            }
+        }
+
         self.get_page_mut()
             .process_key_event(modifiers, key_event, events)
     }
@@ -294,8 +350,9 @@ impl AllPages {
         let response = match event {
             PageEvent::Sdc(change) => self.song_directory_config.ui_change(change),
             PageEvent::Pattern(event) => self.pattern.process_event(event, events),
-            PageEvent::OrderList(event) => self.order_list.process_event(event),
+            PageEvent::OrderList(event) => self.order_list.process_event(event, events),
             PageEvent::SampleList(event) => self.sample_list.process_event(event, events),
+            PageEvent::SampleEditor(event) => self.sample_editor.process_event(event, events),
         };
 
         // if the page isn't shown a redraw isn't necessary
@@ -6,8 +6,10 @@ use torque_tracker_engine::project::song::SongOperation;
 use torque_tracker_engine::{file::impulse_format::header::PatternOrder, project::song::Song};
 use winit::keyboard::{Key, ModifiersState, NamedKey};
 
-use crate::app::{EventQueue, GlobalEvent, send_song_op};
+use crate::app::{EventQueue, GlobalEvent, send_song_msg, send_song_op};
+use crate::history::SongMsg;
 use crate::ui::header::HeaderEvent;
+use crate::ui::pages::pattern::PatternPageEvent;
 use crate::ui::widgets::{NextWidget, StandardResponse, Widget};
 use crate::{
     coordinates::{CharPosition, CharRect},
@@ -21,6 +23,16 @@ pub enum OrderListPageEvent {
     SetVolumeCurrent(i16),
     SetPanCurrent(i16),
     SetPlayback(Option<u16>),
+    /// changed in the song, for example by undo. Doesn't send the change back to the song
+    SetVolume(u8, u8),
+    /// changed in the song. Only Pan::Value can be shown
+    SetPan(u8, Pan),
+    /// from the pattern editor
+    ToggleMute(u8),
+    /// mutes all other channels or unmutes all, if it already was the only one playing
+    Solo(u8),
+    /// changed in the song
+    SetOrder(u16, PatternOrder),
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -52,6 +64,8 @@ pub struct OrderListPage {
     pattern_order: [PatternOrder; Song::MAX_ORDERS],
     volume: [Slider<0, 64, ()>; 64],
     pan: [Slider<0, 64, ()>; 64],
+    /// muted channels have volume 0 in the song. The slider still shows their volume
+    muted: [bool; 64],
 }
 
 impl OrderListPage {
@@ -112,10 +126,15 @@ impl OrderListPage {
                     },
                 )
             }),
+            muted: [false; 64],
         }
     }
 
-    pub fn process_event(&mut self, event: OrderListPageEvent) -> PageResponse {
+    pub fn process_event(
+        &mut self,
+        event: OrderListPageEvent,
+        events: &mut EventQueue<'_>,
+    ) -> PageResponse {
         match event {
             OrderListPageEvent::SetVolumeCurrent(vol) => {
                 let cursor = match self.cursor {
@@ -140,10 +159,51 @@ impl OrderListPage {
                     .expect("the event was created from the slider, so has to fit.")
             }
             OrderListPageEvent::SetPlayback(o) => self.order_playback = o,
+            OrderListPageEvent::SetVolume(channel, vol) => {
+                let valid = self.volume[usize::from(channel)].try_set_silent(i16::from(vol));
+                assert!(valid, "the song only has valid volumes");
+            }
+            OrderListPageEvent::SetPan(channel, pan) => {
+                // surround and disabled can't be shown by the slider yet
+                if let Pan::Value(pan) = pan {
+                    let valid = self.pan[usize::from(channel)].try_set_silent(i16::from(pan));
+                    assert!(valid, "the song only has valid pannings");
+                }
+            }
+            OrderListPageEvent::ToggleMute(channel) => self.toggle_mute(channel, events),
+            OrderListPageEvent::Solo(channel) => {
+                let only_unmuted = self
+                    .muted
+                    .iter()
+                    .enumerate()
+                    .all(|(idx, muted)| *muted != (idx == usize::from(channel)));
+                for idx in 0..64 {
+                    self.set_muted(idx, !only_unmuted && idx != channel, events);
+                }
+            }
+            OrderListPageEvent::SetOrder(idx, order) => {
+                self.pattern_order[usize::from(idx)] = order;
+                self.send_order_len(events);
+            }
         };
         PageResponse::RequestRedraw
     }
 
+    fn toggle_mute(&mut self, channel: u8, events: &mut EventQueue<'_>) {
+        self.set_muted(channel, !self.muted[usize::from(channel)], events);
+    }
+
+    /// isn't undoable. Keeps the pattern page up to date
+    fn set_muted(&mut self, channel: u8, mute: bool, events: &mut EventQueue<'_>) {
+        if self.muted[usize::from(channel)] != mute {
+            self.muted[usize::from(channel)] = mute;
+            send_song_msg(SongMsg::SetMuted(channel, mute));
+            events.push(GlobalEvent::Page(PageEvent::Pattern(
+                PatternPageEvent::SetMuted(channel, mute),
+            )));
+        }
+    }
+
     pub fn switch_mode(&mut self) {
         self.mode = match self.mode {
             Mode::Panning => Mode::Volume,
@@ -259,12 +319,19 @@ impl Page for OrderListPage {
         const CHANNEL_BASE_RIGHT: CharPosition = CharPosition::new(54, 15);
         const CHANNEL: &str = "Channel";
         let mut buf = [0; 2];
+        // muted channels are dimmed
+        let text_color = |channel: usize| if self.muted[channel] { 1 } else { 0 };
         for row in 0..32 {
-            draw_buffer.draw_string(CHANNEL, CHANNEL_BASE_LEFT + CharPosition::new(0, row), 0, 2);
+            draw_buffer.draw_string(
+                CHANNEL,
+                CHANNEL_BASE_LEFT + CharPosition::new(0, row),
+                text_color(row),
+                2,
+            );
             draw_buffer.draw_string(
                 CHANNEL,
                 CHANNEL_BASE_RIGHT + CharPosition::new(0, row),
-                0,
+                text_color(row + 32),
                 2,
             );
             let mut curse: std::io::Cursor<&mut [u8]> = std::io::Cursor::new(&mut buf);
@@ -272,7 +339,7 @@ impl Page for OrderListPage {
             draw_buffer.draw_string(
                 from_utf8(&buf).unwrap(),
                 CHANNEL_BASE_LEFT + CharPosition::new(8, row),
-                0,
+                text_color(row),
                 2,
             );
             let mut curse: std::io::Cursor<&mut [u8]> = std::io::Cursor::new(&mut buf);
@@ -280,7 +347,7 @@ impl Page for OrderListPage {
             draw_buffer.draw_string(
                 from_utf8(&buf).unwrap(),
                 CHANNEL_BASE_RIGHT + CharPosition::new(8, row),
-                0,
+                text_color(row + 32),
                 2,
             );
         }
@@ -522,14 +589,24 @@ impl Page for OrderListPage {
                 }
 
                 match self.mode {
-                    Mode::Panning => match self.pan[usize::from(c - 1)]
-                        .process_input(modifiers, key_event, events)
-                        .standard
-                    {
-                        StandardResponse::SwitchFocus(_) => return PageResponse::None,
-                        StandardResponse::RequestRedraw => return PageResponse::RequestRedraw,
-                        StandardResponse::None => return PageResponse::None,
-                    },
+                    Mode::Panning => {
+                        // space mutes like in the pattern editor with F9
+                        if key_event.logical_key == Key::Named(NamedKey::Space)
+                            && modifiers.state().is_empty()
+                            && key_event.state.is_pressed()
+                        {
+                            self.toggle_mute(c - 1, events);
+                            return PageResponse::RequestRedraw;
+                        }
+                        match self.pan[usize::from(c - 1)]
+                            .process_input(modifiers, key_event, events)
+                            .standard
+                        {
+                            StandardResponse::SwitchFocus(_) => return PageResponse::None,
+                            StandardResponse::RequestRedraw => return PageResponse::RequestRedraw,
+                            StandardResponse::None => return PageResponse::None,
+                        }
+                    }
                     Mode::Volume => match self.volume[usize::from(c - 1)]
                         .process_input(modifiers, key_event, events)
                         .standard
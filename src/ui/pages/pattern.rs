@@ -1,4 +1,10 @@
-use std::{io::Write, str::from_utf8};
+mod selection;
+
+use std::{
+    io::Write,
+    str::from_utf8,
+    time::{Duration, Instant},
+};
 
 use torque_tracker_engine::project::{
     event_command::NoteCommand,
@@ -7,19 +13,29 @@ use torque_tracker_engine::project::{
     song::{Song, SongOperation},
 };
 use winit::{
+    event::KeyEvent,
     event_loop::EventLoopProxy,
-    keyboard::{Key, NamedKey, SmolStr},
+    keyboard::{Key, ModifiersState, NamedKey, PhysicalKey, SmolStr},
 };
 
 use crate::{
-    app::{EXECUTOR, EventQueue, GlobalEvent, SONG_MANAGER, send_song_op},
+    app::{
+        EXECUTOR, EventQueue, GlobalEvent, PlaybackType, SONG_MANAGER, send_song_op, send_song_ops,
+    },
     coordinates::{CharPosition, CharRect},
-    ui::header::HeaderEvent,
+    ui::{
+        dialog::{confirm::ConfirmDialog, pattern_properties::PatternPropertiesDialog},
+        header::{Header, HeaderEvent},
+        note_keys::{MAX_NOTE, NoteKeyLayout, note_from_key, note_in_octave},
+    },
 };
 
-use super::{Page, PageResponse};
+use super::{Page, PageResponse, order_list::OrderListPageEvent};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use selection::{Block, PasteMode, Selection};
+
+// ordered from left to right
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 enum InEventPosition {
     Note,
     Octave,
@@ -62,45 +78,347 @@ impl InEventPosition {
     }
 }
 
-fn get_note_from_key(key: &winit::keyboard::SmolStr, octave: u8) -> Option<Note> {
-    // TODO: keyboard layouts fuckk me.
-    #[allow(clippy::identity_op, clippy::zero_prefixed_literal)]
-    let note = match key.as_str() {
-        // lower octave
-        "z" => Some(Note::new(00 + 12 * (octave - 1))), // C-1
-        "s" => Some(Note::new(01 + 12 * (octave - 1))),
-        "x" => Some(Note::new(02 + 12 * (octave - 1))),
-        "d" => Some(Note::new(03 + 12 * (octave - 1))),
-        "c" => Some(Note::new(04 + 12 * (octave - 1))),
-        "v" => Some(Note::new(05 + 12 * (octave - 1))),
-        "g" => Some(Note::new(06 + 12 * (octave - 1))),
-        "b" => Some(Note::new(07 + 12 * (octave - 1))),
-        "h" => Some(Note::new(08 + 12 * (octave - 1))),
-        "n" => Some(Note::new(09 + 12 * (octave - 1))),
-        "j" => Some(Note::new(10 + 12 * (octave - 1))),
-        "m" => Some(Note::new(11 + 12 * (octave - 1))),
-        // base octave
-        "q" => Some(Note::new(00 + 12 * octave)), // C
-        "2" => Some(Note::new(01 + 12 * octave)), // Db / C#
-        "w" => Some(Note::new(02 + 12 * octave)), // D
-        "3" => Some(Note::new(03 + 12 * octave)), // Eb / D#
-        "e" => Some(Note::new(04 + 12 * octave)), // E
-        "r" => Some(Note::new(05 + 12 * octave)), // F
-        "5" => Some(Note::new(06 + 12 * octave)), // Gb / F#
-        "t" => Some(Note::new(07 + 12 * octave)), // G
-        "6" => Some(Note::new(08 + 12 * octave)), // Ab / G#
-        "y" => Some(Note::new(09 + 12 * octave)), // A
-        "7" => Some(Note::new(10 + 12 * octave)), // Bb / A#
-        "u" => Some(Note::new(11 + 12 * octave)), // B
-        // higher octave
-        "i" => Some(Note::new(0 + 12 * (octave + 1))), // C+1
-        "9" => Some(Note::new(1 + 12 * (octave + 1))),
-        "o" => Some(Note::new(2 + 12 * (octave + 1))),
-        "0" => Some(Note::new(3 + 12 * (octave + 1))),
-        "p" => Some(Note::new(4 + 12 * (octave + 1))),
+/// How much of every channel is shown. Less detail fits more channels on the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum TrackView {
+    Note,
+    NoteSample,
+    NoteSampleVol,
+    #[default]
+    Full,
+}
+
+impl TrackView {
+    /// space for the channels right of the row numbers
+    const AVAILABLE_WIDTH: usize = 74;
+
+    fn next(self) -> Self {
+        match self {
+            TrackView::Note => TrackView::NoteSample,
+            TrackView::NoteSample => TrackView::NoteSampleVol,
+            TrackView::NoteSampleVol => TrackView::Full,
+            TrackView::Full => TrackView::Note,
+        }
+    }
+
+    /// without the space between channels
+    fn width(self) -> usize {
+        match self {
+            TrackView::Note => 3,
+            TrackView::NoteSample => 6,
+            TrackView::NoteSampleVol => 9,
+            TrackView::Full => 13,
+        }
+    }
+
+    /// with the space to the next channel
+    fn channel_width(self) -> usize {
+        self.width() + 1
+    }
+
+    fn drawn_channels(self) -> u8 {
+        u8::try_from(Self::AVAILABLE_WIDTH / self.channel_width()).unwrap()
+    }
+
+    /// rightmost position the cursor can be in
+    fn last_position(self) -> InEventPosition {
+        match self {
+            TrackView::Note => InEventPosition::Octave,
+            TrackView::NoteSample => InEventPosition::Sample2,
+            TrackView::NoteSampleVol => InEventPosition::VolPan2,
+            TrackView::Full => InEventPosition::Effect3,
+        }
+    }
+
+    /// has to fit into width
+    fn channel_heading(self) -> &'static str {
+        match self {
+            TrackView::Note => " ",
+            TrackView::NoteSample => " Ch ",
+            TrackView::NoteSampleVol => " Chan ",
+            TrackView::Full => " Channel ",
+        }
+    }
+}
+
+/// highest value of the volume and panning in the volume column
+const MAX_VOL_PAN: u8 = 64;
+/// the volume column effects only have a single digit
+const MAX_VOL_EFFECT_PARAM: u8 = 9;
+
+/// Impulse Tracker letter and parameter of a volume column effect.
+/// Volume and panning are shown as plain numbers, so they don't have a letter.
+fn vol_effect_to_letter(vol: VolumeEffect) -> Option<(char, u8)> {
+    match vol {
+        VolumeEffect::FineVolSlideUp(p) => Some(('A', p)),
+        VolumeEffect::FineVolSlideDown(p) => Some(('B', p)),
+        VolumeEffect::VolSlideUp(p) => Some(('C', p)),
+        VolumeEffect::VolSlideDown(p) => Some(('D', p)),
+        VolumeEffect::PitchSlideDown(p) => Some(('E', p)),
+        VolumeEffect::PitchSlideUp(p) => Some(('F', p)),
+        VolumeEffect::SlideToNoteWithSpeed(p) => Some(('G', p)),
+        VolumeEffect::VibratoWithSpeed(p) => Some(('H', p)),
+        VolumeEffect::Volume(_) | VolumeEffect::Panning(_) | VolumeEffect::None => None,
+    }
+}
+
+fn vol_effect_from_letter(letter: char, param: u8) -> Option<VolumeEffect> {
+    let param = param.min(MAX_VOL_EFFECT_PARAM);
+    match letter.to_ascii_uppercase() {
+        'A' => Some(VolumeEffect::FineVolSlideUp(param)),
+        'B' => Some(VolumeEffect::FineVolSlideDown(param)),
+        'C' => Some(VolumeEffect::VolSlideUp(param)),
+        'D' => Some(VolumeEffect::VolSlideDown(param)),
+        'E' => Some(VolumeEffect::PitchSlideDown(param)),
+        'F' => Some(VolumeEffect::PitchSlideUp(param)),
+        'G' => Some(VolumeEffect::SlideToNoteWithSpeed(param)),
+        'H' => Some(VolumeEffect::VibratoWithSpeed(param)),
         _ => None,
-    };
-    note.and_then(Result::ok)
+    }
+}
+
+/// Impulse Tracker letter and parameter of an effect command
+fn command_to_letter(command: NoteCommand) -> Option<(char, u8)> {
+    match command {
+        NoteCommand::None => None,
+        NoteCommand::SetTempo(p) => Some(('A', p)),
+        NoteCommand::JumpToOrder(p) => Some(('B', p)),
+        NoteCommand::BreakToRow(p) => Some(('C', p)),
+        NoteCommand::VolumeSlideDown(p) => Some(('D', p)),
+        NoteCommand::PitchSlideDown(p) => Some(('E', p)),
+        NoteCommand::PitchSlideUp(p) => Some(('F', p)),
+        NoteCommand::SlideToNote(p) => Some(('G', p)),
+        NoteCommand::Vibrato(p) => Some(('H', p)),
+        NoteCommand::Tremor(p) => Some(('I', p)),
+        NoteCommand::Arpeggio(p) => Some(('J', p)),
+        NoteCommand::VibratoAndVolSlideDown(p) => Some(('K', p)),
+        NoteCommand::SlideToNoteAndVolSlideDown(p) => Some(('L', p)),
+        NoteCommand::SetChannelVol(p) => Some(('M', p)),
+        NoteCommand::ChannelVolumeSlideDown(p) => Some(('N', p)),
+        NoteCommand::SetSampleOffset(p) => Some(('O', p)),
+        NoteCommand::PanningSlide(p) => Some(('P', p)),
+        NoteCommand::RetriggerNote(p) => Some(('Q', p)),
+        NoteCommand::Tremolo(p) => Some(('R', p)),
+        NoteCommand::AlmostEverything(p) => Some(('S', p)),
+        NoteCommand::TempoChange(p) => Some(('T', p)),
+        NoteCommand::FineVibrato(p) => Some(('U', p)),
+        NoteCommand::SetGlobalVolume(p) => Some(('V', p)),
+        NoteCommand::GlobalVolumeSlide(p) => Some(('W', p)),
+        NoteCommand::SetPanning(p) => Some(('X', p)),
+        NoteCommand::Panbrello(p) => Some(('Y', p)),
+        NoteCommand::MIDIMacros(p) => Some(('Z', p)),
+    }
+}
+
+fn command_from_letter(letter: char, param: u8) -> Option<NoteCommand> {
+    match letter.to_ascii_uppercase() {
+        'A' => Some(NoteCommand::SetTempo(param)),
+        'B' => Some(NoteCommand::JumpToOrder(param)),
+        'C' => Some(NoteCommand::BreakToRow(param)),
+        'D' => Some(NoteCommand::VolumeSlideDown(param)),
+        'E' => Some(NoteCommand::PitchSlideDown(param)),
+        'F' => Some(NoteCommand::PitchSlideUp(param)),
+        'G' => Some(NoteCommand::SlideToNote(param)),
+        'H' => Some(NoteCommand::Vibrato(param)),
+        'I' => Some(NoteCommand::Tremor(param)),
+        'J' => Some(NoteCommand::Arpeggio(param)),
+        'K' => Some(NoteCommand::VibratoAndVolSlideDown(param)),
+        'L' => Some(NoteCommand::SlideToNoteAndVolSlideDown(param)),
+        'M' => Some(NoteCommand::SetChannelVol(param)),
+        'N' => Some(NoteCommand::ChannelVolumeSlideDown(param)),
+        'O' => Some(NoteCommand::SetSampleOffset(param)),
+        'P' => Some(NoteCommand::PanningSlide(param)),
+        'Q' => Some(NoteCommand::RetriggerNote(param)),
+        'R' => Some(NoteCommand::Tremolo(param)),
+        'S' => Some(NoteCommand::AlmostEverything(param)),
+        'T' => Some(NoteCommand::TempoChange(param)),
+        'U' => Some(NoteCommand::FineVibrato(param)),
+        'V' => Some(NoteCommand::SetGlobalVolume(param)),
+        'W' => Some(NoteCommand::GlobalVolumeSlide(param)),
+        'X' => Some(NoteCommand::SetPanning(param)),
+        'Y' => Some(NoteCommand::Panbrello(param)),
+        'Z' => Some(NoteCommand::MIDIMacros(param)),
+        _ => None,
+    }
+}
+
+/// moves the note by the semitones and clamps it to the valid notes. None if the note doesn't change
+fn transpose_note(note: Note, semitones: i16) -> Option<Note> {
+    let new = (i16::from(note.get()) + semitones).clamp(0, i16::from(MAX_NOTE));
+    let new = u8::try_from(new).unwrap();
+    if new == note.get() {
+        return None;
+    }
+    Note::new(new).ok()
+}
+
+/// Slides the volume column or the effect parameter from the top to the bottom row of the selection in every
+/// channel. Both ends need the same kind of volume or effect.
+/// Events always need a note, so only rows that already have an event are changed
+fn interpolate(
+    pattern: &Pattern,
+    selection: Selection,
+    volume_column: bool,
+) -> Vec<(InPatternPosition, Option<NoteEvent>)> {
+    /// a at step 0, b at step steps
+    fn lerp(a: u8, b: u8, step: u16, steps: u16) -> u8 {
+        let (a, b, step, steps) = (
+            u32::from(a),
+            u32::from(b),
+            u32::from(step),
+            u32::from(steps),
+        );
+        let value = (a * (steps - step) + b * step + steps / 2) / steps;
+        u8::try_from(value).unwrap()
+    }
+
+    let (top, bot) = (selection.top(), selection.bot());
+    let mut changes = Vec::new();
+    for channel in selection.channels() {
+        let get = |row| pattern.get_event(InPatternPosition { row, channel });
+        let (Some(first), Some(last)) = (get(top).copied(), get(bot).copied()) else {
+            continue;
+        };
+        for row in top + 1..bot {
+            let Some(&event) = get(row) else {
+                continue;
+            };
+            let (step, steps) = (row - top, bot - top);
+            let new = if volume_column {
+                let vol = match (first.vol, last.vol) {
+                    (VolumeEffect::Volume(a), VolumeEffect::Volume(b)) => {
+                        VolumeEffect::Volume(lerp(a, b, step, steps))
+                    }
+                    (VolumeEffect::Panning(a), VolumeEffect::Panning(b)) => {
+                        VolumeEffect::Panning(lerp(a, b, step, steps))
+                    }
+                    (a, b) => match (vol_effect_to_letter(a), vol_effect_to_letter(b)) {
+                        (Some((letter, a)), Some((other, b))) if letter == other => {
+                            vol_effect_from_letter(letter, lerp(a, b, step, steps))
+                                .expect("letter was created from a volume effect")
+                        }
+                        _ => break,
+                    },
+                };
+                NoteEvent { vol, ..event }
+            } else {
+                let command = match (
+                    command_to_letter(first.command),
+                    command_to_letter(last.command),
+                ) {
+                    (Some((letter, a)), Some((other, b))) if letter == other => {
+                        command_from_letter(letter, lerp(a, b, step, steps))
+                            .expect("letter was created from a command")
+                    }
+                    _ => break,
+                };
+                NoteEvent { command, ..event }
+            };
+            changes.push((InPatternPosition { row, channel }, Some(new)));
+        }
+    }
+    changes
+}
+
+/// moves the events in the channels down by count rows, starting at start.
+/// Events moved past the end of the pattern are lost.
+/// Doesn't apply the changes, only returns them.
+fn insert_rows(
+    pattern: &Pattern,
+    channels: impl Iterator<Item = u8> + Clone,
+    start: u16,
+    count: u16,
+) -> Vec<(InPatternPosition, Option<NoteEvent>)> {
+    let mut changes = Vec::new();
+    for row in (start..pattern.row_count()).rev() {
+        for channel in channels.clone() {
+            let position = InPatternPosition { row, channel };
+            let new = row
+                .checked_sub(count)
+                .filter(|from| *from >= start)
+                .and_then(|row| pattern.get_event(InPatternPosition { row, channel }))
+                .copied();
+            // empty cells that stay empty don't need an operation
+            if new.is_some() || pattern.get_event(position).is_some() {
+                changes.push((position, new));
+            }
+        }
+    }
+    changes
+}
+
+/// moves the events in the channels up by count rows, removing the rows starting at start.
+/// The rows at the end of the pattern become empty.
+/// Doesn't apply the changes, only returns them.
+fn delete_rows(
+    pattern: &Pattern,
+    channels: impl Iterator<Item = u8> + Clone,
+    start: u16,
+    count: u16,
+) -> Vec<(InPatternPosition, Option<NoteEvent>)> {
+    let mut changes = Vec::new();
+    for row in start..pattern.row_count() {
+        for channel in channels.clone() {
+            let position = InPatternPosition { row, channel };
+            let new = row
+                .checked_add(count)
+                .and_then(|row| pattern.get_event(InPatternPosition { row, channel }))
+                .copied();
+            // empty cells that stay empty don't need an operation
+            if new.is_some() || pattern.get_event(position).is_some() {
+                changes.push((position, new));
+            }
+        }
+    }
+    changes
+}
+
+/// spreads the events from top over twice the rows or moves them together into half the rows.
+/// Writes the rows from top to before end. Events that end up after end are lost.
+/// Doesn't apply the changes, only returns them.
+fn scale_rows(
+    pattern: &Pattern,
+    channels: impl Iterator<Item = u8> + Clone,
+    top: u16,
+    end: u16,
+    expand: bool,
+) -> Vec<(InPatternPosition, Option<NoteEvent>)> {
+    let mut changes = Vec::new();
+    for row in top..end {
+        let offset = row - top;
+        let from = if expand {
+            offset.is_multiple_of(2).then_some(top + offset / 2)
+        } else {
+            top.checked_add(offset * 2).filter(|from| *from < end)
+        };
+        for channel in channels.clone() {
+            let position = InPatternPosition { row, channel };
+            let new = from
+                .and_then(|row| pattern.get_event(InPatternPosition { row, channel }))
+                .copied();
+            // empty cells that stay empty don't need an operation
+            if new.is_some() || pattern.get_event(position).is_some() {
+                changes.push((position, new));
+            }
+        }
+    }
+    changes
+}
+
+/// rows per beat and per measure get a lighter background
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowHighlight {
+    pub beat: u16,
+    pub measure: u16,
+}
+
+impl Default for RowHighlight {
+    fn default() -> Self {
+        Self {
+            beat: 4,
+            measure: 16,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -109,6 +427,17 @@ pub enum PatternPageEvent {
     SetSampleInstr(u8),
     /// pattern, row
     PlaybackPosition(Option<(u8, u16)>),
+    /// pattern was changed in the song, for example by undo
+    Changed(u8, PatternOperation),
+    ToggleNoteKeyLayout,
+    /// from the pattern properties dialog. pattern (None for the song default), song default
+    SetRowHighlight(Option<RowHighlight>, RowHighlight),
+    /// from the pattern properties dialog. asks before removing events
+    SetLength(u16),
+    /// the user agreed to remove the events after the new end
+    ConfirmLength,
+    /// copy of the mute state on the order list
+    SetMuted(u8, bool),
 }
 
 #[derive(Debug)]
@@ -120,25 +449,54 @@ pub struct PatternPage {
     event_proxy: EventLoopProxy<GlobalEvent>,
     /// Last used or last selected in the sample menu
     selected_sample_instr: u8,
+    /// copy of the octave in the header
+    octave: u8,
+    note_layout: NoteKeyLayout,
+    /// key that started the note preview. Releasing it stops the note
+    live_note: Option<PhysicalKey>,
+    /// the view follows the playback. The cursor stays where it is
+    follow: bool,
+    /// notes are written at the playing row. The view follows the playback while recording
+    record: bool,
+    /// move recorded notes to the next row when they are pressed late in the row
+    quantize: bool,
+    /// keys held while recording and their channel. Needed to put chords into multiple channels
+    record_keys: Vec<(PhysicalKey, u8)>,
+    /// when the playback reached the current row and how long the last row took. Used for quantizing
+    row_timing: Option<(Instant, Option<Duration>)>,
     /// pattern, row
     // storest the pattern index, because if i switch page i want to show the current position before i
     // get the next event
     playback: Option<(u8, u16)>,
+    /// pattern requested from the song. Following the playback would request it again on every row
+    loading: Option<u8>,
+    /// marked block
+    selection: Option<Selection>,
+    /// last copied block. is kept when switching patterns, so it can be pasted into another one
+    clipboard: Option<Block>,
+    // TODO: store the highlights with the song, so they are saved and loaded with it. The song has no field
+    // for them yet, so until then they are only kept here while the tracker runs
+    /// own row highlight of each pattern
+    highlights: [Option<RowHighlight>; Song::MAX_PATTERNS],
+    /// used by patterns without their own
+    default_highlight: RowHighlight,
+    /// length waiting for the confirm dialog
+    pending_length: Option<u16>,
+    /// copy of the mute state on the order list. Needed for drawing
+    muted: [bool; Self::MAX_CHANNELS as usize],
+    track_view: TrackView,
+    /// rows the cursor moves after entering something
+    edit_step: u8,
 }
 
 impl PatternPage {
     const MAX_PATTERN: usize = 199;
     const DRAWN_ROWS: u16 = 32;
-    const DRAWN_CHANNELS: u8 = 5;
     const MAX_CHANNELS: u8 = 64;
     /// how many rows the cursor is moved when pressing pageup/down
     // TODO: make configurable
     const PAGE_AS_ROWS: u16 = 16;
-    const CHANNEL_WIDTH: usize = 14;
-
-    // TODO: make configurable
-    const ROW_HIGHTLIGHT_MINOR: u16 = 4;
-    const ROW_HIGHTLIGHT_MAJOR: u16 = 16;
+    const MAX_EDIT_STEP: u8 = 16;
 
     pub fn process_event(
         &mut self,
@@ -149,10 +507,9 @@ impl PatternPage {
             PatternPageEvent::Loaded(pattern, idx) => {
                 self.pattern = pattern;
                 self.pattern_index = idx;
+                self.loading = None;
                 events.push(GlobalEvent::Header(HeaderEvent::SetPattern(idx)));
-                events.push(GlobalEvent::Header(HeaderEvent::SetMaxCursorRow(
-                    self.pattern.row_count(),
-                )));
+                self.length_changed(events);
                 PageResponse::RequestRedraw
             }
             PatternPageEvent::SetSampleInstr(i) => {
@@ -160,10 +517,101 @@ impl PatternPage {
                 PageResponse::None
             }
             PatternPageEvent::PlaybackPosition(p) => {
+                if p != self.playback {
+                    let now = Instant::now();
+                    // only consecutive rows give the row length
+                    let row_len = match (self.playback, p, self.row_timing) {
+                        (Some((_, old)), Some((_, new)), Some((start, _))) if new == old + 1 => {
+                            Some(now - start)
+                        }
+                        (_, _, Some((_, len))) => len,
+                        _ => None,
+                    };
+                    self.row_timing = p.map(|_| (now, row_len));
+                }
                 self.playback = p;
+                // recording also follows, so the notes go into the playing pattern
+                if (self.follow || self.record)
+                    && let Some((pattern, row)) = p
+                {
+                    if pattern != self.pattern_index {
+                        self.load_pattern(pattern);
+                    } else {
+                        self.draw_position.row = self.view_start(row);
+                    }
+                }
+                // back to the cursor when the playback stops
+                if p.is_none() {
+                    self.update_draw_position();
+                }
                 // TODO: only return this if the change is actually visible
                 PageResponse::RequestRedraw
             }
+            PatternPageEvent::ToggleNoteKeyLayout => {
+                self.note_layout = self.note_layout.toggle();
+                events.push(GlobalEvent::Header(HeaderEvent::SetNoteKeyLayout(
+                    self.note_layout,
+                )));
+                self.send_note_keys(events);
+                PageResponse::None
+            }
+            PatternPageEvent::Changed(idx, op) => {
+                // other patterns are loaded from the song when they are opened
+                if idx != self.pattern_index {
+                    return PageResponse::None;
+                }
+                match op {
+                    PatternOperation::SetEvent { position, event } => {
+                        self.pattern.set_event(position, event)
+                    }
+                    PatternOperation::RemoveEvent { position } => {
+                        self.pattern.remove_event(position)
+                    }
+                    PatternOperation::SetLength { new_len } => {
+                        self.pattern.set_length(new_len);
+                        self.length_changed(events);
+                    }
+                }
+                PageResponse::RequestRedraw
+            }
+            PatternPageEvent::SetRowHighlight(pattern, song) => {
+                self.highlights[usize::from(self.pattern_index)] = pattern;
+                self.default_highlight = song;
+                PageResponse::RequestRedraw
+            }
+            PatternPageEvent::SetLength(rows) => {
+                if rows == self.pattern.row_count() {
+                    return PageResponse::None;
+                }
+                if self.events_after(rows).next().is_some() {
+                    self.pending_length = Some(rows);
+                    events.push(GlobalEvent::OpenDialog(Box::new(|| {
+                        Box::new(ConfirmDialog::new(
+                            "Remove the notes after the new end?",
+                            || {
+                                Some(GlobalEvent::Page(super::PageEvent::Pattern(
+                                    PatternPageEvent::ConfirmLength,
+                                )))
+                            },
+                            || None,
+                        ))
+                    })));
+                    return PageResponse::None;
+                }
+                self.set_length(rows, events);
+                PageResponse::RequestRedraw
+            }
+            PatternPageEvent::SetMuted(channel, muted) => {
+                self.muted[usize::from(channel)] = muted;
+                PageResponse::RequestRedraw
+            }
+            PatternPageEvent::ConfirmLength => match self.pending_length.take() {
+                Some(rows) => {
+                    self.set_length(rows, events);
+                    PageResponse::RequestRedraw
+                }
+                None => PageResponse::None,
+            },
         }
     }
 
@@ -178,7 +626,24 @@ impl PatternPage {
             draw_position: InPatternPosition { row: 0, channel: 0 },
             event_proxy: proxy,
             selected_sample_instr: 0,
+            octave: Header::DEFAULT_OCTAVE,
+            note_layout: NoteKeyLayout::default(),
+            live_note: None,
+            follow: false,
+            record: false,
+            quantize: false,
+            record_keys: Vec::new(),
+            row_timing: None,
             playback: None,
+            loading: None,
+            selection: None,
+            clipboard: None,
+            highlights: [None; Song::MAX_PATTERNS],
+            default_highlight: RowHighlight::default(),
+            pending_length: None,
+            muted: [false; Self::MAX_CHANNELS as usize],
+            track_view: TrackView::default(),
+            edit_step: 1,
         }
     }
 
@@ -200,23 +665,72 @@ impl PatternPage {
         }
 
         self.cursor_position.0 = pos;
+        self.update_draw_position();
+        true
+    }
 
-        // update draw position
-        if pos.channel >= self.draw_position.channel + Self::DRAWN_CHANNELS {
-            self.draw_position.channel = pos.channel - Self::DRAWN_CHANNELS + 1;
+    fn update_draw_position(&mut self) {
+        let pos = self.cursor_position.0;
+        let drawn_channels = self.track_view.drawn_channels();
+        if pos.channel >= self.draw_position.channel + drawn_channels {
+            self.draw_position.channel = pos.channel - drawn_channels + 1;
         } else if pos.channel < self.draw_position.channel {
             self.draw_position.channel = pos.channel
         }
+        // a view with more channels could go past the last one
+        self.draw_position.channel = self
+            .draw_position
+            .channel
+            .min(Self::MAX_CHANNELS - drawn_channels);
 
-        if pos.row <= (Self::DRAWN_ROWS / 2) {
-            self.draw_position.row = 0;
-        } else if pos.row >= self.pattern.row_count() - (Self::DRAWN_ROWS / 2) {
-            self.draw_position.row = self.pattern.row_count() - Self::DRAWN_ROWS
-        } else {
-            self.draw_position.row = pos.row - (Self::DRAWN_ROWS / 2);
-        }
+        self.draw_position.row = self.view_start(pos.row);
+    }
 
-        true
+    /// first drawn row, so the row is in the middle, except at the start and end of the pattern.
+    /// patterns shorter than the view start at the top
+    fn view_start(&self, row: u16) -> u16 {
+        let last_start = self.pattern.row_count().saturating_sub(Self::DRAWN_ROWS);
+        row.saturating_sub(Self::DRAWN_ROWS / 2).min(last_start)
+    }
+
+    /// positions of the events at or after the row
+    fn events_after(&self, row: u16) -> impl Iterator<Item = InPatternPosition> + '_ {
+        (row..self.pattern.row_count())
+            .flat_map(|row| {
+                (0..Self::MAX_CHANNELS).map(move |channel| InPatternPosition { row, channel })
+            })
+            .filter(|pos| self.pattern.get_event(*pos).is_some())
+    }
+
+    /// removes the events after the new end first, so undo can bring them back
+    fn set_length(&mut self, rows: u16, events: &mut EventQueue<'_>) {
+        let mut ops: Vec<SongOperation> = self
+            .events_after(rows)
+            .map(|position| {
+                SongOperation::PatternOperation(
+                    self.pattern_index,
+                    PatternOperation::RemoveEvent { position },
+                )
+            })
+            .collect();
+        ops.push(SongOperation::PatternOperation(
+            self.pattern_index,
+            PatternOperation::SetLength { new_len: rows },
+        ));
+        self.pattern.set_length(rows);
+        send_song_ops(ops);
+        self.length_changed(events);
+    }
+
+    /// keeps the cursor, selection and header inside of the pattern
+    fn length_changed(&mut self, events: &mut EventQueue<'_>) {
+        let rows = self.pattern.row_count();
+        events.push(GlobalEvent::Header(HeaderEvent::SetMaxCursorRow(rows)));
+        if self.selection.is_some_and(|s| s.bot() >= rows) {
+            self.selection = None;
+        }
+        self.set_cursor(self.cursor_position.0, events);
+        self.update_draw_position();
     }
 
     /// returns true if the cursor was changed
@@ -226,7 +740,25 @@ impl PatternPage {
         self.set_cursor(pos, events)
     }
 
+    /// after entering something. Moves by the edit step instead of a single row
+    fn cursor_edit_step(&mut self, events: &mut EventQueue<'_>) {
+        let mut pos = self.cursor_position.0;
+        pos.row = pos.row.saturating_add(u16::from(self.edit_step));
+        self.set_cursor(pos, events);
+    }
+
+    fn set_edit_step(&mut self, edit_step: u8, events: &mut EventQueue<'_>) {
+        self.edit_step = edit_step.min(Self::MAX_EDIT_STEP);
+        events.push(GlobalEvent::Header(HeaderEvent::SetEditStep(
+            self.edit_step,
+        )));
+    }
+
     fn load_pattern(&mut self, idx: u8) {
+        if self.loading == Some(idx) {
+            return;
+        }
+        self.loading = Some(idx);
         let proxy = self.event_proxy.clone();
         EXECUTOR
             .spawn(async move {
@@ -260,6 +792,392 @@ impl PatternPage {
         send_song_op(op);
     }
 
+    /// sets or removes all the events and sends them to the song as one batch
+    fn set_events(&mut self, changes: Vec<(InPatternPosition, Option<NoteEvent>)>) {
+        let ops = self.apply_changes(changes);
+        send_song_ops(ops);
+    }
+
+    /// sets or removes the events only in the page. Returns the operations for the song
+    fn apply_changes(
+        &mut self,
+        changes: Vec<(InPatternPosition, Option<NoteEvent>)>,
+    ) -> Vec<SongOperation> {
+        changes
+            .into_iter()
+            .map(|(position, event)| {
+                let op = match event {
+                    Some(event) => {
+                        self.pattern.set_event(position, event);
+                        PatternOperation::SetEvent { position, event }
+                    }
+                    None => {
+                        self.pattern.remove_event(position);
+                        PatternOperation::RemoveEvent { position }
+                    }
+                };
+                SongOperation::PatternOperation(self.pattern_index, op)
+            })
+            .collect()
+    }
+
+    /// Alt-F and Alt-G. Works on the selection or without one on the whole pattern.
+    /// On the whole pattern the length can be doubled or halved with it
+    fn scale_block(&mut self, expand: bool, change_length: bool, events: &mut EventQueue<'_>) {
+        let row_count = self.pattern.row_count();
+        let (top, end, channels) = match self.selection {
+            Some(s) => (s.top(), s.bot() + 1, s.channels()),
+            None => (0, row_count, 0..=Self::MAX_CHANNELS - 1),
+        };
+        let new_length = (change_length && self.selection.is_none()).then(|| {
+            if expand {
+                // the engine only accepts lengths below the maximum
+                (row_count * 2).min(Pattern::MAX_ROWS - 1)
+            } else {
+                row_count.div_ceil(2)
+            }
+        });
+
+        let mut ops = Vec::new();
+        // the pattern has to be long enough before the events can be moved
+        if expand && let Some(rows) = new_length {
+            self.pattern.set_length(rows);
+            ops.push(SongOperation::PatternOperation(
+                self.pattern_index,
+                PatternOperation::SetLength { new_len: rows },
+            ));
+        }
+        let end = if expand {
+            new_length.unwrap_or(end)
+        } else {
+            end
+        };
+        let changes = scale_rows(&self.pattern, channels, top, end, expand);
+        ops.extend(self.apply_changes(changes));
+        // the rows that are cut off are empty now
+        if !expand && let Some(rows) = new_length {
+            self.pattern.set_length(rows);
+            ops.push(SongOperation::PatternOperation(
+                self.pattern_index,
+                PatternOperation::SetLength { new_len: rows },
+            ));
+        }
+        send_song_ops(ops);
+        if new_length.is_some() {
+            self.length_changed(events);
+        }
+    }
+
+    /// current selection or the event under the cursor
+    fn selection_or_cursor(&self) -> Selection {
+        self.selection.unwrap_or(Selection::new(
+            self.cursor_position.0,
+            self.cursor_position.0,
+        ))
+    }
+
+    /// moves the cursor and the end of the selection.
+    /// Starts a new selection if the cursor isn't at the end of the current one.
+    fn extend_selection(&mut self, pos: InPatternPosition, events: &mut EventQueue<'_>) {
+        let old = self.cursor_position.0;
+        let start = match self.selection {
+            Some(selection) if selection.end == old => selection.start,
+            _ => old,
+        };
+        self.set_cursor(pos, events);
+        self.selection = Some(Selection::new(start, self.cursor_position.0));
+    }
+
+    /// cursor position after a movement key. Only used for extending the selection
+    fn moved_cursor(&self, key: NamedKey) -> Option<InPatternPosition> {
+        let mut pos = self.cursor_position.0;
+        match key {
+            NamedKey::ArrowUp => pos.row = pos.row.saturating_sub(1),
+            NamedKey::ArrowDown => pos.row = pos.row.saturating_add(1),
+            NamedKey::ArrowLeft => pos.channel = pos.channel.saturating_sub(1),
+            NamedKey::ArrowRight => pos.channel = pos.channel.saturating_add(1),
+            NamedKey::PageUp => pos.row = pos.row.saturating_sub(Self::PAGE_AS_ROWS),
+            NamedKey::PageDown => pos.row = pos.row.saturating_add(Self::PAGE_AS_ROWS),
+            _ => return None,
+        }
+        Some(pos)
+    }
+
+    fn paste(&mut self, mode: PasteMode) {
+        let Some(block) = self.clipboard.take() else {
+            return;
+        };
+        let cursor = self.cursor_position.0;
+        let rows = cursor.row..(cursor.row + block.rows()).min(self.pattern.row_count());
+        let channels = cursor.channel..(cursor.channel + block.channels()).min(Self::MAX_CHANNELS);
+
+        let mut changes = match mode {
+            PasteMode::Insert => {
+                insert_rows(&self.pattern, channels.clone(), cursor.row, block.rows())
+            }
+            PasteMode::Overwrite | PasteMode::Mix => Vec::new(),
+        };
+        for row in rows {
+            for channel in channels.clone() {
+                let position = InPatternPosition { row, channel };
+                let event = block.get_event(InPatternPosition {
+                    row: row - cursor.row,
+                    channel: channel - cursor.channel,
+                });
+                match mode {
+                    // the insert already emptied the space
+                    PasteMode::Insert => {
+                        if event.is_some() {
+                            changes.push((position, event));
+                        }
+                    }
+                    PasteMode::Overwrite => {
+                        if event.is_some() || self.pattern.get_event(position).is_some() {
+                            changes.push((position, event));
+                        }
+                    }
+                    PasteMode::Mix => {
+                        if event.is_some() && self.pattern.get_event(position).is_none() {
+                            changes.push((position, event));
+                        }
+                    }
+                }
+            }
+        }
+        self.clipboard = Some(block);
+        self.set_events(changes);
+    }
+
+    /// moves the notes in the selection or at the cursor by the semitones. Clamps to the valid notes
+    fn transpose(&mut self, semitones: i16) {
+        let changes = self
+            .selection_or_cursor()
+            .positions()
+            .filter_map(|pos| {
+                let event = *self.pattern.get_event(pos)?;
+                let note = transpose_note(event.note, semitones)?;
+                Some((pos, Some(NoteEvent { note, ..event })))
+            })
+            .collect();
+        self.set_events(changes);
+    }
+
+    /// Alt-K on the selection. The cursor decides between the volume column and the effect
+    fn interpolate(&mut self) {
+        let Some(selection) = self.selection else {
+            return;
+        };
+        let volume_column = matches!(
+            self.cursor_position.1,
+            InEventPosition::VolPan1 | InEventPosition::VolPan2
+        );
+        let changes = interpolate(&self.pattern, selection, volume_column);
+        self.set_events(changes);
+    }
+
+    /// Alt + key commands that work on blocks. Shift only changes the transpose commands
+    fn process_block_key(
+        &mut self,
+        key: &str,
+        shift: bool,
+        events: &mut EventQueue<'_>,
+    ) -> PageResponse {
+        let cursor = self.cursor_position.0;
+        match key.to_lowercase().as_str() {
+            // start and end of the block
+            "b" => {
+                let end = self.selection.map_or(cursor, |s| s.end);
+                self.selection = Some(Selection::new(cursor, end));
+            }
+            "e" => {
+                let start = self.selection.map_or(cursor, |s| s.start);
+                self.selection = Some(Selection::new(start, cursor));
+            }
+            // unmark
+            "u" => self.selection = None,
+            // first press marks the channel, second press the whole pattern
+            "l" => {
+                let last_row = self.pattern.row_count() - 1;
+                let channel = Selection::new(
+                    InPatternPosition {
+                        row: 0,
+                        channel: cursor.channel,
+                    },
+                    InPatternPosition {
+                        row: last_row,
+                        channel: cursor.channel,
+                    },
+                );
+                self.selection = if self.selection == Some(channel) {
+                    Some(Selection::new(
+                        InPatternPosition { row: 0, channel: 0 },
+                        InPatternPosition {
+                            row: last_row,
+                            channel: Self::MAX_CHANNELS - 1,
+                        },
+                    ))
+                } else {
+                    Some(channel)
+                };
+            }
+            // copy
+            "c" => {
+                self.clipboard = Some(Block::copy(&self.pattern, self.selection_or_cursor()));
+                return PageResponse::None;
+            }
+            // cut
+            "z" => {
+                let selection = self.selection_or_cursor();
+                let block = Block::copy(&self.pattern, selection);
+                let changes = selection
+                    .positions()
+                    .filter(|pos| self.pattern.get_event(*pos).is_some())
+                    .map(|pos| (pos, None))
+                    .collect();
+                self.set_events(changes);
+                self.clipboard = Some(block);
+                self.selection = None;
+            }
+            // paste
+            "p" => self.paste(PasteMode::Insert),
+            "o" => self.paste(PasteMode::Overwrite),
+            "m" => self.paste(PasteMode::Mix),
+            // transpose by a semitone or with shift by an octave
+            "q" => self.transpose(if shift { 12 } else { 1 }),
+            "a" => self.transpose(if shift { -12 } else { -1 }),
+            "k" => self.interpolate(),
+            // double or halve the rows. With shift the pattern length changes too
+            "f" => self.scale_block(true, shift, events),
+            "g" => self.scale_block(false, shift, events),
+            // not a block command, but the alt keys all end up here
+            "t" => {
+                self.track_view = self.track_view.next();
+                self.cursor_position.1 =
+                    self.cursor_position.1.min(self.track_view.last_position());
+                self.update_draw_position();
+            }
+            // edit step. digits set 0 - 9, plus and minus go up to the maximum
+            "+" => self.set_edit_step(self.edit_step.saturating_add(1), events),
+            "-" => self.set_edit_step(self.edit_step.saturating_sub(1), events),
+            digit @ ("0" | "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9") => {
+                self.set_edit_step(digit.parse().unwrap(), events)
+            }
+            _ => return PageResponse::None,
+        }
+        PageResponse::RequestRedraw
+    }
+
+    fn send_record(&self, events: &mut EventQueue<'_>) {
+        events.push(GlobalEvent::Header(HeaderEvent::SetRecord(
+            self.record,
+            self.quantize,
+        )));
+    }
+
+    /// writes the note at the playing row. Every held key gets its own channel, starting at the cursor channel.
+    /// The engine only plays a single live note, so of a chord only the last pressed key is heard
+    fn record_note(&mut self, note: Note, row: u16, key: PhysicalKey, events: &mut EventQueue<'_>) {
+        let mut row = row;
+        if self.quantize
+            && let Some((start, Some(len))) = self.row_timing
+            && start.elapsed() > len / 2
+            && row + 1 < self.pattern.row_count()
+        {
+            row += 1;
+        }
+        let Some(channel) = (self.cursor_position.0.channel..Self::MAX_CHANNELS)
+            .find(|c| !self.record_keys.iter().any(|(_, held)| held == c))
+        else {
+            return;
+        };
+        self.record_keys.push((key, channel));
+
+        let position = InPatternPosition { row, channel };
+        let event = match self.pattern.get_event(position) {
+            Some(&event) => NoteEvent { note, ..event },
+            None => NoteEvent {
+                note,
+                sample_instr: self.selected_sample_instr,
+                vol: VolumeEffect::None,
+                command: NoteCommand::None,
+            },
+        };
+        self.set_event(position, event);
+        self.live_note = Some(key);
+        events.push(GlobalEvent::Playback(PlaybackType::LiveNote(event)));
+    }
+
+    /// sets the note at the cursor and previews it. While recording it is written into the playing row instead
+    fn enter_note(
+        &mut self,
+        note: Note,
+        key_event: &KeyEvent,
+        events: &mut EventQueue<'_>,
+    ) -> PageResponse {
+        // only during playback. Otherwise notes are entered normally
+        if self.record && self.playback.is_some() {
+            // the playing pattern is still being loaded when it isn't the shown one
+            if let Some((pattern, row)) = self.playback
+                && pattern == self.pattern_index
+                && !key_event.repeat
+            {
+                self.record_note(note, row, key_event.physical_key, events);
+                return PageResponse::RequestRedraw;
+            }
+            return PageResponse::None;
+        }
+        let event = match self.pattern.get_event(self.cursor_position.0) {
+            None => NoteEvent {
+                note,
+                sample_instr: self.selected_sample_instr,
+                vol: VolumeEffect::None,
+                command: NoteCommand::None,
+            },
+            Some(&event) => NoteEvent { note, ..event },
+        };
+        self.set_event(self.cursor_position.0, event);
+        // holding the key shouldn't restart the note
+        if !key_event.repeat {
+            self.live_note = Some(key_event.physical_key);
+            events.push(GlobalEvent::Playback(PlaybackType::LiveNote(event)));
+        }
+        self.cursor_edit_step(events);
+        PageResponse::RequestRedraw
+    }
+
+    /// "4" plays the note under the cursor until the key is released
+    fn play_cursor_note(&mut self, key_event: &KeyEvent, events: &mut EventQueue<'_>) {
+        if let Some(event) = self.pattern.get_event(self.cursor_position.0) {
+            self.live_note = Some(key_event.physical_key);
+            events.push(GlobalEvent::Playback(PlaybackType::LiveNote(*event)));
+        }
+    }
+
+    pub fn stop_live_note(&mut self, events: &mut EventQueue<'_>) {
+        self.record_keys.clear();
+        if self.live_note.take().is_some() {
+            events.push(GlobalEvent::Playback(PlaybackType::StopLiveNote));
+        }
+    }
+
+    /// one octave up or down. The header shows it and the sample list uses it too
+    pub fn change_octave(&mut self, up: bool, events: &mut EventQueue<'_>) {
+        self.octave = if up {
+            (self.octave + 1).min(Header::MAX_OCTAVE)
+        } else {
+            self.octave.saturating_sub(1)
+        };
+        events.push(GlobalEvent::Header(HeaderEvent::SetOctave(self.octave)));
+        self.send_note_keys(events);
+    }
+
+    /// the sample list also plays notes, so it needs the same keys
+    fn send_note_keys(&self, events: &mut EventQueue<'_>) {
+        events.push(GlobalEvent::Page(super::PageEvent::SampleList(
+            super::SampleListEvent::SetNoteKeys(self.octave, self.note_layout),
+        )));
+    }
+
     pub fn set_sample(&mut self, sample: u8, events: &mut EventQueue<'_>) {
         self.selected_sample_instr = sample;
         events.push(GlobalEvent::Page(super::PageEvent::SampleList(
@@ -272,14 +1190,30 @@ impl Page for PatternPage {
     fn draw(&mut self, draw_buffer: &mut super::DrawBuffer) {
         // helper fns
         fn visible_channels(page: &PatternPage) -> impl Iterator<Item = (usize, u8)> {
-            (page.draw_position.channel..page.draw_position.channel + PatternPage::DRAWN_CHANNELS)
+            (page.draw_position.channel
+                ..page.draw_position.channel + page.track_view.drawn_channels())
                 .enumerate()
         }
 
         fn visible_rows(page: &PatternPage) -> impl Iterator<Item = (usize, u16)> {
-            (page.draw_position.row..page.draw_position.row + PatternPage::DRAWN_ROWS).enumerate()
+            let end = page.draw_position.row + PatternPage::DRAWN_ROWS;
+            (page.draw_position.row..end.min(page.pattern.row_count())).enumerate()
         }
 
+        let channel_width = self.track_view.channel_width();
+
+        // the view and pattern length change, so everything is cleared. Short patterns leave rows
+        // empty and the channels can move
+        draw_buffer.draw_rect(
+            2,
+            CharRect::new(
+                14,
+                14 + usize::from(Self::DRAWN_ROWS),
+                1,
+                4 + TrackView::AVAILABLE_WIDTH,
+            ),
+        );
+
         // draw row numbers
         assert!(self.draw_position.row + Self::DRAWN_ROWS <= 999);
         let mut buf = [0; 3];
@@ -301,18 +1235,32 @@ impl Page for PatternPage {
         }
 
         // draw channel headings
-        assert!(self.draw_position.channel + Self::DRAWN_CHANNELS <= 99);
+        assert!(self.draw_position.channel + self.track_view.drawn_channels() <= 99);
         let mut buf: [u8; 2] = [0; 2];
+        let heading = self.track_view.channel_heading();
         for (index, value) in visible_channels(self) {
-            const BASE_POS: CharPosition = CharPosition::new(14, 14);
+            const BASE_POS: CharPosition = CharPosition::new(5, 14);
+            // muted channels are dimmed
+            let text_color = if self.muted[usize::from(value)] { 2 } else { 3 };
+            let pos = BASE_POS + (index * channel_width, 0);
 
             let mut curse: std::io::Cursor<&mut [u8]> = std::io::Cursor::new(&mut buf);
             write!(&mut curse, "{:02}", value).unwrap();
 
+            draw_buffer.draw_rect(
+                1,
+                CharRect::new(
+                    pos.y(),
+                    pos.y(),
+                    pos.x(),
+                    pos.x() + self.track_view.width() - 1,
+                ),
+            );
+            draw_buffer.draw_string(heading, pos, text_color, 1);
             draw_buffer.draw_string(
                 from_utf8(&buf).unwrap(),
-                BASE_POS + (index * Self::CHANNEL_WIDTH, 0),
-                3,
+                pos + (heading.len(), 0),
+                text_color,
                 1,
             );
         }
@@ -327,6 +1275,7 @@ impl Page for PatternPage {
             sample2: [u8; 8],
             vol_pan1: [u8; 8],
             vol_pan2: [u8; 8],
+            vol_pan_color: u8,
             effect1: [u8; 8],
             effect2: [u8; 8],
             effect3: [u8; 8],
@@ -342,6 +1291,7 @@ impl Page for PatternPage {
                     sample2: BLOCK_CODE,
                     vol_pan1: BLOCK_CODE,
                     vol_pan2: BLOCK_CODE,
+                    vol_pan_color: FOREGROUND,
                     effect1: font8x8::UnicodeFonts::get(&font8x8::BASIC_FONTS, '.').unwrap(),
                     effect2: font8x8::UnicodeFonts::get(&font8x8::BASIC_FONTS, '0').unwrap(),
                     effect3: font8x8::UnicodeFonts::get(&font8x8::BASIC_FONTS, '0').unwrap(),
@@ -351,20 +1301,11 @@ impl Page for PatternPage {
 
         impl From<NoteEvent> for EventView {
             fn from(value: NoteEvent) -> Self {
+                /// also used for the hex digits of the effect parameters
                 fn get_bitmap_digit(value: u8) -> [u8; 8] {
-                    let char = match value {
-                        0 => '0',
-                        1 => '1',
-                        2 => '2',
-                        3 => '3',
-                        4 => '4',
-                        5 => '5',
-                        6 => '6',
-                        7 => '7',
-                        8 => '8',
-                        9 => '9',
-                        _ => panic!("not a digit. number too large"),
-                    };
+                    let char = char::from_digit(u32::from(value), 16)
+                        .expect("not a digit. number too large")
+                        .to_ascii_uppercase();
                     font8x8::UnicodeFonts::get(&font8x8::BASIC_FONTS, char).unwrap()
                 }
 
@@ -380,7 +1321,34 @@ impl Page for PatternPage {
                 // sample_instr number
                 view.sample1 = get_bitmap_digit(value.sample_instr / 10);
                 view.sample2 = get_bitmap_digit(value.sample_instr % 10);
-                // TODO: rest noch
+                // volume column
+                match value.vol {
+                    VolumeEffect::None => (),
+                    VolumeEffect::Volume(v) => {
+                        view.vol_pan1 = get_bitmap_digit(v / 10);
+                        view.vol_pan2 = get_bitmap_digit(v % 10);
+                    }
+                    // same digits as volume, so it needs another color to be distinguishable
+                    VolumeEffect::Panning(p) => {
+                        view.vol_pan1 = get_bitmap_digit(p / 10);
+                        view.vol_pan2 = get_bitmap_digit(p % 10);
+                        view.vol_pan_color = PANNING_COLOR;
+                    }
+                    effect => {
+                        if let Some((letter, param)) = vol_effect_to_letter(effect) {
+                            view.vol_pan1 =
+                                font8x8::UnicodeFonts::get(&font8x8::BASIC_FONTS, letter).unwrap();
+                            view.vol_pan2 = get_bitmap_digit(param);
+                        }
+                    }
+                }
+                // effect command
+                if let Some((letter, param)) = command_to_letter(value.command) {
+                    view.effect1 =
+                        font8x8::UnicodeFonts::get(&font8x8::BASIC_FONTS, letter).unwrap();
+                    view.effect2 = get_bitmap_digit(param >> 4);
+                    view.effect3 = get_bitmap_digit(param & 0xF);
+                }
                 view
             }
         }
@@ -388,12 +1356,23 @@ impl Page for PatternPage {
         const EVENT_BASE_POS: CharPosition = CharPosition::new(5, 15);
         const BACKGROUND: u8 = 0;
         const FOREGROUND: u8 = 6;
+        const PANNING_COLOR: u8 = 10;
+        const SELECTION_COLOR: u8 = 8;
+        let highlight =
+            self.highlights[usize::from(self.pattern_index)].unwrap_or(self.default_highlight);
         for (c_idx, c_val) in visible_channels(self) {
             for (r_idx, r_val) in visible_rows(self) {
+                let selected = self.selection.is_some_and(|s| {
+                    s.contains(InPatternPosition {
+                        row: r_val,
+                        channel: c_val,
+                    })
+                });
                 let background_color = match r_val {
+                    _ if selected => SELECTION_COLOR,
                     val if val == self.cursor_position.0.row => 1,
-                    val if val % Self::ROW_HIGHTLIGHT_MAJOR == 0 => 14,
-                    val if val % Self::ROW_HIGHTLIGHT_MINOR == 0 => 15,
+                    val if val % highlight.measure == 0 => 14,
+                    val if val % highlight.beat == 0 => 15,
                     _ => BACKGROUND,
                 };
                 let view: EventView = self
@@ -404,16 +1383,35 @@ impl Page for PatternPage {
                     })
                     .map(|e| (*e).into())
                     .unwrap_or_default();
-                let pos = EVENT_BASE_POS + (c_idx * Self::CHANNEL_WIDTH, r_idx);
+                let pos = EVENT_BASE_POS + (c_idx * channel_width, r_idx);
                 draw_buffer.draw_char(view.note1, pos, 6, background_color);
                 draw_buffer.draw_char(view.note2, pos + (1, 0), FOREGROUND, background_color);
                 draw_buffer.draw_char(view.octave, pos + (2, 0), FOREGROUND, background_color);
+                if self.track_view == TrackView::Note {
+                    continue;
+                }
                 draw_buffer.draw_rect(background_color, (pos + (3, 0)).into());
                 draw_buffer.draw_char(view.sample1, pos + (4, 0), FOREGROUND, background_color);
                 draw_buffer.draw_char(view.sample2, pos + (5, 0), FOREGROUND, background_color);
+                if self.track_view == TrackView::NoteSample {
+                    continue;
+                }
                 draw_buffer.draw_rect(background_color, (pos + (6, 0)).into());
-                draw_buffer.draw_char(view.vol_pan1, pos + (7, 0), FOREGROUND, background_color);
-                draw_buffer.draw_char(view.vol_pan2, pos + (8, 0), FOREGROUND, background_color);
+                draw_buffer.draw_char(
+                    view.vol_pan1,
+                    pos + (7, 0),
+                    view.vol_pan_color,
+                    background_color,
+                );
+                draw_buffer.draw_char(
+                    view.vol_pan2,
+                    pos + (8, 0),
+                    view.vol_pan_color,
+                    background_color,
+                );
+                if self.track_view == TrackView::NoteSampleVol {
+                    continue;
+                }
                 draw_buffer.draw_rect(background_color, (pos + (9, 0)).into());
                 draw_buffer.draw_char(view.effect1, pos + (10, 0), FOREGROUND, background_color);
                 draw_buffer.draw_char(view.effect2, pos + (11, 0), FOREGROUND, background_color);
@@ -421,17 +1419,21 @@ impl Page for PatternPage {
             }
         }
 
-        // draw cursor
+        // draw cursor. Following the playback can move the view away from it
+        if !(self.draw_position.row..self.draw_position.row + Self::DRAWN_ROWS)
+            .contains(&self.cursor_position.0.row)
+        {
+            return;
+        }
         let view: EventView = self
             .pattern
             .get_event(self.cursor_position.0)
             .map(|e| (*e).into())
             .unwrap_or_default();
         assert!(self.cursor_position.0.channel >= self.draw_position.channel);
-        assert!(self.cursor_position.0.row >= self.draw_position.row);
         let c_idx = self.cursor_position.0.channel - self.draw_position.channel;
         let r_idx = self.cursor_position.0.row - self.draw_position.row;
-        let pos = EVENT_BASE_POS + (c_idx as usize * Self::CHANNEL_WIDTH, r_idx as usize);
+        let pos = EVENT_BASE_POS + (c_idx as usize * channel_width, r_idx as usize);
         match self.cursor_position.1 {
             InEventPosition::Note => draw_buffer.draw_char(view.note1, pos, 0, 3),
             InEventPosition::Octave => draw_buffer.draw_char(view.octave, pos + (2, 0), 0, 3),
@@ -446,16 +1448,8 @@ impl Page for PatternPage {
     }
 
     fn draw_constant(&mut self, draw_buffer: &mut super::DrawBuffer) {
+        // the channel headers depend on the track view and mute state, so they are drawn with the pattern
         draw_buffer.draw_rect(2, CharRect::PAGE_AREA);
-
-        // draw channel headers const parts
-        for index in 0..Self::DRAWN_CHANNELS as usize {
-            const BASE_POS: CharPosition = CharPosition::new(5, 14);
-            let pos = BASE_POS + (index * Self::CHANNEL_WIDTH, 0);
-            draw_buffer.draw_rect(1, (pos + (11, 0)).into());
-
-            draw_buffer.draw_string(" Channel ", pos, 3, 1);
-        }
     }
 
     fn process_key_event(
@@ -465,9 +1459,113 @@ impl Page for PatternPage {
         events: &mut EventQueue<'_>,
     ) -> PageResponse {
         if !key_event.state.is_pressed() {
+            self.record_keys
+                .retain(|(key, _)| *key != key_event.physical_key);
+            if self.live_note == Some(key_event.physical_key) {
+                self.live_note = None;
+                events.push(GlobalEvent::Playback(PlaybackType::StopLiveNote));
+            }
+            return PageResponse::None;
+        }
+
+        if key_event.logical_key == Key::Named(NamedKey::ScrollLock) {
+            self.follow = !self.follow;
+            events.push(GlobalEvent::Header(HeaderEvent::SetFollow(self.follow)));
+            if !self.follow {
+                // back to the cursor
+                self.update_draw_position();
+                return PageResponse::RequestRedraw;
+            }
             return PageResponse::None;
         }
 
+        // Ctrl-R toggles recording, Ctrl-Shift-R quantization
+        if modifiers.state().control_key()
+            && !modifiers.state().alt_key()
+            && let Key::Character(char) = &key_event.logical_key
+            && char.eq_ignore_ascii_case("r")
+        {
+            if modifiers.state().shift_key() {
+                self.quantize = !self.quantize;
+            } else {
+                self.record = !self.record;
+                self.record_keys.clear();
+            }
+            self.send_record(events);
+            if !self.record {
+                // recording followed the playback, so the view goes back to the cursor
+                self.update_draw_position();
+                return PageResponse::RequestRedraw;
+            }
+            return PageResponse::None;
+        }
+
+        // the order list owns the mute state and tells the engine
+        if modifiers.state().is_empty() && key_event.logical_key == Key::Named(NamedKey::F9) {
+            events.push(GlobalEvent::Page(super::PageEvent::OrderList(
+                OrderListPageEvent::ToggleMute(self.cursor_position.0.channel),
+            )));
+            return PageResponse::None;
+        } else if modifiers.state().is_empty() && key_event.logical_key == Key::Named(NamedKey::F10)
+        {
+            events.push(GlobalEvent::Page(super::PageEvent::OrderList(
+                OrderListPageEvent::Solo(self.cursor_position.0.channel),
+            )));
+            return PageResponse::None;
+        }
+
+        // insert and delete a row in the channel or with alt in all channels
+        if let Key::Named(key @ (NamedKey::Insert | NamedKey::Delete)) = key_event.logical_key
+            && (modifiers.state().is_empty() || modifiers.state() == ModifiersState::ALT)
+        {
+            let cursor = self.cursor_position.0;
+            let channels = if modifiers.state().alt_key() {
+                0..Self::MAX_CHANNELS
+            } else {
+                cursor.channel..cursor.channel + 1
+            };
+            let changes = if key == NamedKey::Insert {
+                insert_rows(&self.pattern, channels, cursor.row, 1)
+            } else {
+                delete_rows(&self.pattern, channels, cursor.row, 1)
+            };
+            self.set_events(changes);
+            return PageResponse::RequestRedraw;
+        }
+
+        if modifiers.state() == ModifiersState::CONTROL
+            && key_event.logical_key == Key::Named(NamedKey::F2)
+        {
+            let rows = self.pattern.row_count();
+            let pattern = self.highlights[usize::from(self.pattern_index)];
+            let song = self.default_highlight;
+            events.push(GlobalEvent::OpenDialog(Box::new(move || {
+                Box::new(PatternPropertiesDialog::new(rows, pattern, song))
+            })));
+            return PageResponse::None;
+        }
+
+        if (modifiers.state() == ModifiersState::ALT
+            || modifiers.state() == ModifiersState::ALT | ModifiersState::SHIFT)
+            && let Key::Character(char) = &key_event.logical_key
+        {
+            return self.process_block_key(char, modifiers.state().shift_key(), events);
+        } else if modifiers.state() == ModifiersState::SHIFT
+            && let Key::Named(named) = key_event.logical_key
+            && let Some(pos) = self.moved_cursor(named)
+        {
+            self.extend_selection(pos, events);
+            return PageResponse::RequestRedraw;
+        }
+
+        // before the other keys, because with the physical layout a note key can be any character.
+        // For example E is "." on Dvorak and G# is "-" on AZERTY
+        if self.cursor_position.1 == InEventPosition::Note
+            && let Some(note) = note_from_key(key_event, self.note_layout, self.octave)
+        {
+            return self.enter_note(note, key_event, events);
+        }
+
         if key_event.logical_key == Key::Character(SmolStr::new_static("+")) {
             if usize::from(self.pattern_index) != Self::MAX_PATTERN {
                 self.load_pattern(self.pattern_index + 1);
@@ -489,7 +1587,12 @@ impl Page for PatternPage {
                 return PageResponse::RequestRedraw;
             }
         } else if key_event.logical_key == Key::Named(NamedKey::ArrowRight) {
-            match self.cursor_position.1.to_right() {
+            match self
+                .cursor_position
+                .1
+                .to_right()
+                .filter(|p| *p <= self.track_view.last_position())
+            {
                 Some(p) => {
                     self.cursor_position.1 = p;
                     return PageResponse::RequestRedraw;
@@ -513,7 +1616,7 @@ impl Page for PatternPage {
                     let mut pos = self.cursor_position.0;
                     pos.channel = pos.channel.saturating_sub(1);
                     if self.set_cursor(pos, events) {
-                        self.cursor_position.1 = InEventPosition::Effect3;
+                        self.cursor_position.1 = self.track_view.last_position();
                         return PageResponse::RequestRedraw;
                     }
                 }
@@ -554,30 +1657,74 @@ impl Page for PatternPage {
                 return PageResponse::RequestRedraw;
             }
         } else if Key::Character(SmolStr::new_static(".")) == key_event.logical_key {
-            self.remove_event(self.cursor_position.0);
-            self.cursor_next_row(events);
-            return PageResponse::RequestRedraw;
-        } else if let Key::Character(char) = &key_event.logical_key {
-            // should be copied from the header, where this can already be set
-            const DEFAULT_OCTAVE: u8 = 5;
             match self.cursor_position.1 {
-                InEventPosition::Note => {
-                    // TODO: make octave configurable
-                    if let Some(note) = get_note_from_key(char, DEFAULT_OCTAVE) {
-                        let old_event = self.pattern.get_event(self.cursor_position.0);
-                        let event = match old_event {
-                            None => NoteEvent {
-                                note,
-                                sample_instr: self.selected_sample_instr,
+                // only clear the volume column
+                InEventPosition::VolPan1 | InEventPosition::VolPan2 => {
+                    if let Some(event) = self.pattern.get_event(self.cursor_position.0).copied() {
+                        self.set_event(
+                            self.cursor_position.0,
+                            NoteEvent {
                                 vol: VolumeEffect::None,
+                                ..event
+                            },
+                        );
+                    }
+                }
+                // only clear the effect
+                InEventPosition::Effect1 | InEventPosition::Effect2 | InEventPosition::Effect3 => {
+                    if let Some(event) = self.pattern.get_event(self.cursor_position.0).copied() {
+                        self.set_event(
+                            self.cursor_position.0,
+                            NoteEvent {
                                 command: NoteCommand::None,
+                                ..event
                             },
-                            Some(&event) => NoteEvent { note, ..event },
-                        };
-                        self.set_event(self.cursor_position.0, event);
+                        );
                     }
+                }
+                _ => self.remove_event(self.cursor_position.0),
+            }
+            self.cursor_edit_step(events);
+            return PageResponse::RequestRedraw;
+        } else if Key::Character(SmolStr::new_static("`")) == key_event.logical_key
+            && matches!(
+                self.cursor_position.1,
+                InEventPosition::VolPan1 | InEventPosition::VolPan2
+            )
+        {
+            // switch the volume column between volume and panning
+            if let Some(event) = self.pattern.get_event(self.cursor_position.0).copied() {
+                let vol = match event.vol {
+                    VolumeEffect::Volume(v) => VolumeEffect::Panning(v),
+                    VolumeEffect::Panning(p) => VolumeEffect::Volume(p),
+                    _ => return PageResponse::None,
+                };
+                self.set_event(self.cursor_position.0, NoteEvent { vol, ..event });
+                return PageResponse::RequestRedraw;
+            }
+        } else if let Key::Character(char) = &key_event.logical_key {
+            // the other columns take the digits as values. Works while recording too.
+            // TODO: "8" plays the whole row. The engine only plays a single live note, so it can't yet
+            if char == "4"
+                && matches!(
+                    self.cursor_position.1,
+                    InEventPosition::Note | InEventPosition::Effect1
+                )
+            {
+                self.play_cursor_note(key_event, events);
+                self.cursor_next_row(events);
+                return PageResponse::RequestRedraw;
+            }
+            match self.cursor_position.1 {
+                // the note keys were handled already
+                InEventPosition::Note if self.record && self.playback.is_some() => {
+                    return PageResponse::None;
+                }
+                InEventPosition::Note => {
+                    // TODO: note off, note cut and note fade. The engine plays every note value as a pitch,
+                    // so they can't end the note in the channel yet
                     // move to next row even if
-                    self.cursor_next_row(events);
+                    self.cursor_edit_step(events);
                     // always redraw is incorrect. I only need to redraw if either the cursor moved, or the event changed
                     return PageResponse::RequestRedraw;
                 }
@@ -586,13 +1733,14 @@ impl Page for PatternPage {
                         let mut new_event = *event;
                         // set octave fn needed
                         let octave: Result<u8, _> = char.as_str().parse();
-                        if let Ok(octave) = octave {
-                            new_event.note =
-                                Note::new(event.note.get() % 12 + 12 * octave).unwrap();
+                        if let Ok(octave) = octave
+                            && let Some(note) = note_in_octave(event.note.get() % 12, octave)
+                        {
+                            new_event.note = note;
                             self.set_event(self.cursor_position.0, new_event);
                         }
                     }
-                    self.cursor_next_row(events);
+                    self.cursor_edit_step(events);
                     // always redraw is incorrect.
                     return PageResponse::RequestRedraw;
                 }
@@ -636,17 +1784,279 @@ impl Page for PatternPage {
                             self.set_sample(sample_instr, events);
                         }
                     }
-                    self.cursor_next_row(events);
+                    self.cursor_edit_step(events);
+                    return PageResponse::RequestRedraw;
+                }
+                InEventPosition::VolPan1 => {
+                    if let Some(event) = self.pattern.get_event(self.cursor_position.0).copied() {
+                        let vol = if let Ok(num) = char.as_str().parse::<u8>() {
+                            // keep panning if it was set, otherwise it's a volume
+                            match event.vol {
+                                VolumeEffect::Panning(old) => Some(VolumeEffect::Panning(
+                                    (num * 10 + old % 10).min(MAX_VOL_PAN),
+                                )),
+                                VolumeEffect::Volume(old) => Some(VolumeEffect::Volume(
+                                    (num * 10 + old % 10).min(MAX_VOL_PAN),
+                                )),
+                                _ => Some(VolumeEffect::Volume((num * 10).min(MAX_VOL_PAN))),
+                            }
+                        } else {
+                            // keep the parameter when switching between effects
+                            let param = vol_effect_to_letter(event.vol).map_or(0, |(_, p)| p);
+                            char.chars()
+                                .next()
+                                .and_then(|letter| vol_effect_from_letter(letter, param))
+                        };
+                        if let Some(vol) = vol {
+                            self.set_event(self.cursor_position.0, NoteEvent { vol, ..event });
+                        }
+                    }
+                    self.cursor_position.1 = InEventPosition::VolPan2;
+                    return PageResponse::RequestRedraw;
+                }
+                InEventPosition::VolPan2 => {
+                    let num: Result<u8, _> = char.as_str().parse();
+                    if let Ok(num) = num
+                        && let Some(event) = self.pattern.get_event(self.cursor_position.0).copied()
+                    {
+                        let vol = match event.vol {
+                            VolumeEffect::None => Some(VolumeEffect::Volume(num)),
+                            VolumeEffect::Volume(old) => {
+                                Some(VolumeEffect::Volume((old / 10 * 10 + num).min(MAX_VOL_PAN)))
+                            }
+                            VolumeEffect::Panning(old) => Some(VolumeEffect::Panning(
+                                (old / 10 * 10 + num).min(MAX_VOL_PAN),
+                            )),
+                            effect => vol_effect_to_letter(effect)
+                                .and_then(|(letter, _)| vol_effect_from_letter(letter, num)),
+                        };
+                        if let Some(vol) = vol {
+                            self.set_event(self.cursor_position.0, NoteEvent { vol, ..event });
+                        }
+                    }
+                    self.cursor_edit_step(events);
+                    return PageResponse::RequestRedraw;
+                }
+                InEventPosition::Effect1 => {
+                    if let Some(event) = self.pattern.get_event(self.cursor_position.0).copied()
+                        && let Some(letter) = char.chars().next()
+                    {
+                        // keep the parameter when changing the effect
+                        let param = command_to_letter(event.command).map_or(0, |(_, p)| p);
+                        if let Some(command) = command_from_letter(letter, param) {
+                            self.set_event(self.cursor_position.0, NoteEvent { command, ..event });
+                        }
+                    }
+                    self.cursor_position.1 = InEventPosition::Effect2;
+                    return PageResponse::RequestRedraw;
+                }
+                InEventPosition::Effect2 => {
+                    let num = u8::from_str_radix(char.as_str(), 16);
+                    if let Ok(num) = num
+                        && let Some(event) = self.pattern.get_event(self.cursor_position.0).copied()
+                        && let Some((letter, param)) = command_to_letter(event.command)
+                    {
+                        let command = command_from_letter(letter, (num << 4) | (param & 0xF))
+                            .expect("letter was created from a command");
+                        self.set_event(self.cursor_position.0, NoteEvent { command, ..event });
+                    }
+                    self.cursor_position.1 = InEventPosition::Effect3;
+                    return PageResponse::RequestRedraw;
+                }
+                InEventPosition::Effect3 => {
+                    let num = u8::from_str_radix(char.as_str(), 16);
+                    if let Ok(num) = num
+                        && let Some(event) = self.pattern.get_event(self.cursor_position.0).copied()
+                        && let Some((letter, param)) = command_to_letter(event.command)
+                    {
+                        let command = command_from_letter(letter, (param & 0xF0) | num)
+                            .expect("letter was created from a command");
+                        self.set_event(self.cursor_position.0, NoteEvent { command, ..event });
+                    }
+                    self.cursor_edit_step(events);
                     return PageResponse::RequestRedraw;
                 }
-                InEventPosition::VolPan1 => eprintln!("not yet implemented"),
-                InEventPosition::VolPan2 => eprintln!("not yet implemented"),
-                InEventPosition::Effect1 => eprintln!("not yet implemented"),
-                InEventPosition::Effect2 => eprintln!("not yet implemented"),
-                InEventPosition::Effect3 => eprintln!("not yet implemented"),
             }
         }
 
         PageResponse::None
     }
 }
+
+#[cfg(test)]
+mod test {
+    use torque_tracker_engine::project::{
+        event_command::NoteCommand,
+        note_event::{Note, NoteEvent, VolumeEffect},
+        pattern::{InPatternPosition, Pattern},
+    };
+
+    use super::{
+        MAX_NOTE, Selection, command_from_letter, command_to_letter, delete_rows, insert_rows,
+        interpolate, scale_rows, transpose_note, vol_effect_from_letter, vol_effect_to_letter,
+    };
+
+    #[test]
+    fn vol_effect_letters_round_trip() {
+        for letter in 'A'..='H' {
+            let effect = vol_effect_from_letter(letter, 5).unwrap();
+            assert_eq!(vol_effect_to_letter(effect), Some((letter, 5)));
+        }
+        assert!(vol_effect_from_letter('I', 5).is_none());
+        // lower case is accepted, but shown in upper case
+        let effect = vol_effect_from_letter('h', 3).unwrap();
+        assert!(matches!(effect, VolumeEffect::VibratoWithSpeed(3)));
+    }
+
+    #[test]
+    fn vol_effect_param_is_one_digit() {
+        let effect = vol_effect_from_letter('C', 20).unwrap();
+        assert_eq!(vol_effect_to_letter(effect), Some(('C', 9)));
+        assert!(vol_effect_to_letter(VolumeEffect::Volume(20)).is_none());
+        assert!(vol_effect_to_letter(VolumeEffect::Panning(20)).is_none());
+    }
+
+    #[test]
+    fn command_letters_match_impulse_numbers() {
+        // the engine numbers the commands like the Impulse Tracker format, starting at 1 for A
+        for (number, letter) in (1..).zip('A'..='Z') {
+            let command = NoteCommand::try_from((number, 0x42)).ok().unwrap();
+            assert_eq!(command_to_letter(command), Some((letter, 0x42)));
+            let command = command_from_letter(letter.to_ascii_lowercase(), 0x42).unwrap();
+            assert_eq!(command_to_letter(command), Some((letter, 0x42)));
+        }
+        assert!(command_to_letter(NoteCommand::None).is_none());
+        assert!(command_from_letter('.', 0).is_none());
+    }
+
+    #[test]
+    fn transpose_clamps() {
+        let note = |value| Note::new(value).unwrap();
+        assert_eq!(transpose_note(note(60), 12), Some(note(72)));
+        assert_eq!(transpose_note(note(5), -12), Some(note(0)));
+        assert_eq!(transpose_note(note(MAX_NOTE - 1), 12), Some(note(MAX_NOTE)));
+        // already at the edge
+        assert_eq!(transpose_note(note(0), -1), None);
+        assert_eq!(transpose_note(note(MAX_NOTE), 1), None);
+    }
+
+    fn pos(row: u16, channel: u8) -> InPatternPosition {
+        InPatternPosition { row, channel }
+    }
+
+    #[test]
+    fn interpolate_volume_and_effect() {
+        let mut pattern = Pattern::default();
+        let event = |vol, command| NoteEvent {
+            vol,
+            command,
+            ..NoteEvent::default()
+        };
+        pattern.set_event(
+            pos(0, 0),
+            event(VolumeEffect::Volume(0), NoteCommand::Vibrato(0x10)),
+        );
+        pattern.set_event(pos(1, 0), event(VolumeEffect::None, NoteCommand::None));
+        // rows without an event stay empty
+        pattern.set_event(pos(3, 0), event(VolumeEffect::None, NoteCommand::None));
+        pattern.set_event(
+            pos(4, 0),
+            event(VolumeEffect::Volume(64), NoteCommand::Vibrato(0x50)),
+        );
+        let selection = Selection::new(pos(0, 0), pos(4, 0));
+
+        let changes = interpolate(&pattern, selection, true);
+        let volumes: Vec<_> = changes
+            .iter()
+            .map(|(pos, event)| match event.unwrap().vol {
+                VolumeEffect::Volume(v) => (pos.row, v),
+                other => panic!("{other:?}"),
+            })
+            .collect();
+        assert_eq!(volumes, [(1, 16), (3, 48)]);
+
+        let changes = interpolate(&pattern, selection, false);
+        let params: Vec<_> = changes
+            .iter()
+            .map(|(pos, event)| (pos.row, command_to_letter(event.unwrap().command)))
+            .collect();
+        assert_eq!(params, [(1, Some(('H', 0x20))), (3, Some(('H', 0x40)))]);
+    }
+
+    #[test]
+    fn interpolate_needs_matching_ends() {
+        let mut pattern = Pattern::default();
+        let vol = |vol| NoteEvent {
+            vol,
+            ..NoteEvent::default()
+        };
+        pattern.set_event(pos(0, 0), vol(VolumeEffect::Volume(0)));
+        pattern.set_event(pos(1, 0), vol(VolumeEffect::None));
+        pattern.set_event(pos(2, 0), vol(VolumeEffect::Panning(64)));
+        let selection = Selection::new(pos(0, 0), pos(2, 0));
+        assert!(interpolate(&pattern, selection, true).is_empty());
+        assert!(interpolate(&pattern, selection, false).is_empty());
+    }
+
+    /// events told apart by their sample
+    fn pattern_with_samples(events: &[(u16, u8, u8)]) -> Pattern {
+        let mut pattern = Pattern::default();
+        for (row, channel, sample_instr) in events {
+            let event = NoteEvent {
+                sample_instr: *sample_instr,
+                ..NoteEvent::default()
+            };
+            pattern.set_event(pos(*row, *channel), event);
+        }
+        pattern
+    }
+
+    fn samples(changes: &[(InPatternPosition, Option<NoteEvent>)]) -> Vec<(u16, Option<u8>)> {
+        changes
+            .iter()
+            .map(|(pos, event)| (pos.row, event.map(|e| e.sample_instr)))
+            .collect()
+    }
+
+    #[test]
+    fn insert_rows_moves_events_down() {
+        let pattern = pattern_with_samples(&[(0, 0, 1), (2, 0, 2), (63, 0, 3), (2, 1, 4)]);
+        let changes = insert_rows(&pattern, 0..=0, 1, 2);
+        // the event in the last row is pushed out, the one above start stays
+        assert_eq!(samples(&changes), [(63, None), (4, Some(2)), (2, None)]);
+    }
+
+    #[test]
+    fn delete_rows_moves_events_up() {
+        let pattern = pattern_with_samples(&[(0, 0, 1), (2, 0, 2), (63, 0, 3), (2, 1, 4)]);
+        let changes = delete_rows(&pattern, 0..=0, 1, 1);
+        assert_eq!(
+            samples(&changes),
+            [(1, Some(2)), (2, None), (62, Some(3)), (63, None)]
+        );
+    }
+
+    #[test]
+    fn scale_rows_spreads_and_gathers() {
+        let pattern = pattern_with_samples(&[(1, 0, 1), (2, 0, 2), (3, 0, 3), (6, 0, 4)]);
+        // rows 1 to 4 are spread over 1 to 8
+        let changes = scale_rows(&pattern, 0..=0, 1, 9, true);
+        assert_eq!(
+            samples(&changes),
+            [
+                (1, Some(1)),
+                (2, None),
+                (3, Some(2)),
+                (5, Some(3)),
+                (6, None)
+            ]
+        );
+
+        // rows 0, 2, 4 and 6 move to 0 to 3, the rest until 7 is cleared
+        let changes = scale_rows(&pattern, 0..=0, 0, 8, false);
+        assert_eq!(
+            samples(&changes),
+            [(1, Some(2)), (2, None), (3, Some(4)), (6, None)]
+        );
+    }
+}
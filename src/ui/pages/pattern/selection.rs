@@ -0,0 +1,162 @@
+use std::ops::RangeInclusive;
+
+use torque_tracker_engine::project::{
+    note_event::NoteEvent,
+    pattern::{InPatternPosition, Pattern},
+};
+
+/// Marked rectangle of rows and channels. Both corners are inclusive and don't need to be in any order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    pub start: InPatternPosition,
+    pub end: InPatternPosition,
+}
+
+impl Selection {
+    pub fn new(start: InPatternPosition, end: InPatternPosition) -> Self {
+        Self { start, end }
+    }
+
+    pub fn top(&self) -> u16 {
+        self.start.row.min(self.end.row)
+    }
+
+    pub fn bot(&self) -> u16 {
+        self.start.row.max(self.end.row)
+    }
+
+    pub fn left(&self) -> u8 {
+        self.start.channel.min(self.end.channel)
+    }
+
+    pub fn right(&self) -> u8 {
+        self.start.channel.max(self.end.channel)
+    }
+
+    pub fn rows(&self) -> RangeInclusive<u16> {
+        self.top()..=self.bot()
+    }
+
+    pub fn channels(&self) -> RangeInclusive<u8> {
+        self.left()..=self.right()
+    }
+
+    pub fn contains(&self, pos: InPatternPosition) -> bool {
+        self.rows().contains(&pos.row) && self.channels().contains(&pos.channel)
+    }
+
+    /// all positions inside, row by row
+    pub fn positions(self) -> impl Iterator<Item = InPatternPosition> {
+        let channels = self.channels();
+        self.rows().flat_map(move |row| {
+            channels
+                .clone()
+                .map(move |channel| InPatternPosition { row, channel })
+        })
+    }
+}
+
+/// How a Block is written into the pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasteMode {
+    /// moves the existing events down to make space for the block
+    Insert,
+    /// replaces everything in the area of the block, also with empty cells
+    Overwrite,
+    /// only writes into empty cells
+    Mix,
+}
+
+/// Copied pattern data. Positions are relative to the top left corner of the copied selection.
+#[derive(Debug, Clone)]
+pub struct Block {
+    rows: u16,
+    channels: u8,
+    events: Vec<(InPatternPosition, NoteEvent)>,
+}
+
+impl Block {
+    pub fn copy(pattern: &Pattern, selection: Selection) -> Self {
+        let events = selection
+            .positions()
+            .filter_map(|pos| {
+                pattern.get_event(pos).map(|event| {
+                    let relative = InPatternPosition {
+                        row: pos.row - selection.top(),
+                        channel: pos.channel - selection.left(),
+                    };
+                    (relative, *event)
+                })
+            })
+            .collect();
+        Self {
+            rows: selection.bot() - selection.top() + 1,
+            channels: selection.right() - selection.left() + 1,
+            events,
+        }
+    }
+
+    pub fn rows(&self) -> u16 {
+        self.rows
+    }
+
+    pub fn channels(&self) -> u8 {
+        self.channels
+    }
+
+    pub fn get_event(&self, pos: InPatternPosition) -> Option<NoteEvent> {
+        self.events
+            .iter()
+            .find(|(p, _)| *p == pos)
+            .map(|(_, event)| *event)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use torque_tracker_engine::project::{
+        note_event::{Note, NoteEvent},
+        pattern::{InPatternPosition, Pattern},
+    };
+
+    use super::{Block, Selection};
+
+    fn pos(row: u16, channel: u8) -> InPatternPosition {
+        InPatternPosition { row, channel }
+    }
+
+    #[test]
+    fn corners_in_any_order() {
+        let selection = Selection::new(pos(7, 1), pos(3, 4));
+        assert_eq!((selection.top(), selection.bot()), (3, 7));
+        assert_eq!((selection.left(), selection.right()), (1, 4));
+        assert!(selection.contains(pos(3, 1)));
+        assert!(selection.contains(pos(7, 4)));
+        assert!(!selection.contains(pos(8, 4)));
+        assert!(!selection.contains(pos(5, 0)));
+    }
+
+    #[test]
+    fn positions_row_by_row() {
+        let positions: Vec<_> = Selection::new(pos(1, 3), pos(0, 2)).positions().collect();
+        assert_eq!(positions, [pos(0, 2), pos(0, 3), pos(1, 2), pos(1, 3)]);
+    }
+
+    #[test]
+    fn block_is_relative_to_top_left() {
+        let mut pattern = Pattern::default();
+        let event = NoteEvent {
+            note: Note::new(60).unwrap(),
+            ..NoteEvent::default()
+        };
+        pattern.set_event(pos(5, 3), event);
+        // outside of the selection
+        pattern.set_event(pos(9, 3), event);
+
+        let block = Block::copy(&pattern, Selection::new(pos(6, 4), pos(4, 2)));
+        assert_eq!((block.rows(), block.channels()), (3, 3));
+        assert_eq!(block.get_event(pos(1, 1)).map(|e| e.note), Some(event.note));
+        assert!(block.get_event(pos(0, 0)).is_none());
+        assert!(block.get_event(pos(5, 1)).is_none());
+    }
+}
@@ -0,0 +1,487 @@
+use std::ops::Range;
+
+use torque_tracker_engine::sample::Sample;
+use winit::keyboard::{Key, ModifiersState, NamedKey, SmolStr};
+
+use crate::{
+    app::{EventQueue, GlobalEvent},
+    coordinates::{CharPosition, CharRect, PixelRect},
+    draw_buffer::DrawBuffer,
+    ui::{
+        dialog::slider_dialog::SliderDialog,
+        pages::{Page, PageEvent, PageResponse, SampleListEvent},
+        waveform::{draw_marker, draw_waveform, frame, frame_count, frame_to_x},
+    },
+};
+
+#[derive(Debug, Clone)]
+pub enum SampleEditorEvent {
+    /// the sample selected in the sample list. None for empty slots
+    SetSample(u8, Option<Sample>),
+    /// percent from the amplify dialog
+    Amplify(i16),
+}
+
+/// changes to the sample data. Work on the selection, or the whole sample if nothing is selected
+#[derive(Debug, Clone, Copy)]
+enum Edit {
+    Cut,
+    Crop,
+    Silence,
+    Reverse,
+    Normalize,
+    Amplify(f32),
+    Invert,
+    RemoveDcOffset,
+    FadeIn,
+    FadeOut,
+    /// always the whole sample
+    ToggleStereo,
+}
+
+impl Edit {
+    /// None if the result would be an empty sample
+    fn apply(self, data: &[f32], stereo: bool, range: Range<usize>) -> Option<(Vec<f32>, bool)> {
+        let channels = if stereo { 2 } else { 1 };
+        let samples = range.start * channels..range.end * channels;
+        let mut data = data.to_vec();
+        let selected = &mut data[samples.clone()];
+        match self {
+            Edit::Cut => {
+                data.drain(samples);
+            }
+            Edit::Crop => data = data[samples].to_vec(),
+            Edit::Silence => selected.fill(0.),
+            Edit::Reverse => {
+                selected.reverse();
+                // reversing the samples also swapped left and right
+                if stereo {
+                    selected
+                        .chunks_exact_mut(2)
+                        .for_each(|frame| frame.swap(0, 1));
+                }
+            }
+            Edit::Normalize => {
+                let peak = selected.iter().fold(0_f32, |peak, v| peak.max(v.abs()));
+                if peak > 0. {
+                    selected.iter_mut().for_each(|v| *v /= peak);
+                }
+            }
+            Edit::Amplify(factor) => selected
+                .iter_mut()
+                .for_each(|v| *v = (*v * factor).clamp(-1., 1.)),
+            Edit::Invert => selected.iter_mut().for_each(|v| *v = -*v),
+            Edit::RemoveDcOffset => {
+                let frames = selected.len() / channels;
+                for channel in 0..channels {
+                    let offset = selected.iter().skip(channel).step_by(channels).sum::<f32>()
+                        / frames as f32;
+                    selected
+                        .iter_mut()
+                        .skip(channel)
+                        .step_by(channels)
+                        .for_each(|v| *v -= offset);
+                }
+            }
+            Edit::FadeIn | Edit::FadeOut => {
+                let frames = selected.len() / channels;
+                for (i, frame) in selected.chunks_exact_mut(channels).enumerate() {
+                    let position = if matches!(self, Edit::FadeIn) {
+                        i
+                    } else {
+                        frames - 1 - i
+                    };
+                    let gain = position as f32 / frames as f32;
+                    frame.iter_mut().for_each(|v| *v *= gain);
+                }
+            }
+            Edit::ToggleStereo => {
+                data = if stereo {
+                    data.chunks_exact(2)
+                        .map(|frame| (frame[0] + frame[1]) / 2.)
+                        .collect()
+                } else {
+                    data.iter().flat_map(|v| [*v, *v]).collect()
+                };
+                return Some((data, !stereo));
+            }
+        }
+        (!data.is_empty()).then_some((data, stereo))
+    }
+}
+
+/// interleaved data of the sample without the padding
+fn unpadded_data(sample: &Sample) -> Vec<f32> {
+    let frames = 0..frame_count(sample);
+    if sample.is_mono() {
+        frames.map(|idx| frame(sample, idx)[0]).collect()
+    } else {
+        frames.flat_map(|idx| frame(sample, idx)).collect()
+    }
+}
+
+/// Shift-F3. Edits the sample selected in the sample list
+pub struct SampleEditor {
+    selected: u8,
+    sample: Option<Sample>,
+    /// frames shown in the waveform
+    view: Range<usize>,
+    cursor: usize,
+    /// other end of the selection. The cursor is the first
+    mark: Option<usize>,
+    /// the waveform is only drawn again after it changed
+    waveform_changed: bool,
+}
+
+impl SampleEditor {
+    const WAVEFORM_RECT: CharRect = CharRect::new(15, 40, 2, 77);
+    /// fewest frames that can be shown when zooming in
+    const MIN_VIEW: usize = 16;
+    const MAX_AMPLIFY: i16 = 400;
+    /// enough for the longest sample
+    const FRAME_DIGITS: usize = Sample::MAX_LENGTH.ilog10() as usize + 1;
+
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            sample: None,
+            view: 0..0,
+            cursor: 0,
+            mark: None,
+            waveform_changed: true,
+        }
+    }
+
+    pub fn process_event(
+        &mut self,
+        event: SampleEditorEvent,
+        events: &mut EventQueue<'_>,
+    ) -> PageResponse {
+        match event {
+            SampleEditorEvent::SetSample(idx, sample) => {
+                let len = sample.as_ref().map(frame_count).unwrap_or(0);
+                // a different sample starts fully zoomed out
+                if idx != self.selected || self.view.end > len {
+                    self.view = 0..len;
+                    self.cursor = 0;
+                    self.mark = None;
+                }
+                self.selected = idx;
+                self.sample = sample;
+                self.cursor = self.cursor.min(len);
+                self.mark = self.mark.filter(|mark| *mark <= len);
+                self.waveform_changed = true;
+                PageResponse::RequestRedraw
+            }
+            SampleEditorEvent::Amplify(percent) => {
+                self.edit(Edit::Amplify(f32::from(percent) / 100.), events);
+                PageResponse::RequestRedraw
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.sample.as_ref().map(frame_count).unwrap_or(0)
+    }
+
+    /// None if nothing is selected
+    fn selection(&self) -> Option<Range<usize>> {
+        self.mark
+            .map(|mark| mark.min(self.cursor)..mark.max(self.cursor))
+            .filter(|selection| !selection.is_empty())
+    }
+
+    /// sends the changed sample to the sample list, which sends it to the song
+    fn edit(&mut self, edit: Edit, events: &mut EventQueue<'_>) {
+        let Some(sample) = &self.sample else {
+            return;
+        };
+        let range = self.selection().unwrap_or(0..frame_count(sample));
+        let Some((data, stereo)) =
+            edit.apply(&unpadded_data(sample), !sample.is_mono(), range.clone())
+        else {
+            return;
+        };
+        // both add the padding again
+        let sample = if stereo {
+            Sample::new_stereo_interpolated(data)
+        } else {
+            Sample::new_mono(data)
+        };
+        // the frames after the selection moved, so the selection isn't valid anymore
+        if matches!(edit, Edit::Cut | Edit::Crop) {
+            self.mark = None;
+            self.cursor = if matches!(edit, Edit::Cut) {
+                range.start
+            } else {
+                0
+            };
+            self.view = 0..frame_count(&sample);
+        }
+        events.push(GlobalEvent::Page(PageEvent::SampleList(
+            SampleListEvent::SetSampleData(self.selected, sample),
+        )));
+    }
+
+    /// frames moved per key press. One pixel column of the waveform
+    fn cursor_step(&self) -> usize {
+        (self.view.len()
+            / PixelRect::from(Self::WAVEFORM_RECT)
+                .horizontal_range()
+                .count())
+        .max(1)
+    }
+
+    /// moves the view so the cursor is visible
+    fn set_cursor(&mut self, cursor: usize, select: bool) {
+        if select {
+            self.mark.get_or_insert(self.cursor);
+        } else {
+            self.mark = None;
+        }
+        self.cursor = cursor.min(self.len());
+        let view_len = self.view.len();
+        if self.cursor < self.view.start {
+            self.view = self.cursor..self.cursor + view_len;
+        } else if self.cursor > self.view.end {
+            self.view = self.cursor - view_len..self.cursor;
+        }
+        self.waveform_changed = true;
+    }
+
+    /// zooms around the cursor
+    fn zoom(&mut self, zoom_in: bool) {
+        let len = self.len();
+        let view_len = if zoom_in {
+            (self.view.len() / 2).max(Self::MIN_VIEW.min(len))
+        } else {
+            (self.view.len() * 2).min(len)
+        };
+        let start = self.cursor.saturating_sub(view_len / 2).min(len - view_len);
+        self.view = start..start + view_len;
+        self.waveform_changed = true;
+    }
+
+    fn process_edit_key(&mut self, char: &str, events: &mut EventQueue<'_>) -> PageResponse {
+        let edit = if char.eq_ignore_ascii_case("c") {
+            Edit::Crop
+        } else if char.eq_ignore_ascii_case("z") {
+            Edit::Silence
+        } else if char.eq_ignore_ascii_case("r") {
+            Edit::Reverse
+        } else if char.eq_ignore_ascii_case("n") {
+            Edit::Normalize
+        } else if char.eq_ignore_ascii_case("i") {
+            Edit::Invert
+        } else if char.eq_ignore_ascii_case("d") {
+            Edit::RemoveDcOffset
+        } else if char.eq_ignore_ascii_case("f") {
+            Edit::FadeIn
+        } else if char.eq_ignore_ascii_case("g") {
+            Edit::FadeOut
+        } else if char.eq_ignore_ascii_case("t") {
+            Edit::ToggleStereo
+        } else if char.eq_ignore_ascii_case("m") {
+            events.push(GlobalEvent::OpenDialog(Box::new(|| {
+                Box::new(SliderDialog::with_value(
+                    100,
+                    0..=SampleEditor::MAX_AMPLIFY,
+                    |n| GlobalEvent::Page(PageEvent::SampleEditor(SampleEditorEvent::Amplify(n))),
+                ))
+            })));
+            return PageResponse::None;
+        } else {
+            return PageResponse::None;
+        };
+        self.edit(edit, events);
+        PageResponse::None
+    }
+}
+
+impl Page for SampleEditor {
+    fn draw(&mut self, draw_buffer: &mut DrawBuffer) {
+        const CURSOR_COLOR: u8 = 3;
+
+        let stereo = match &self.sample {
+            Some(sample) if !sample.is_mono() => "Stereo",
+            Some(_) => "Mono  ",
+            None => "Empty ",
+        };
+        let selection = self
+            .selection()
+            .map(|s| format!("{:02$}-{:02$}", s.start, s.end, Self::FRAME_DIGITS))
+            .unwrap_or(format!("{:1$}", "", Self::FRAME_DIGITS * 2 + 1));
+        draw_buffer.draw_string(
+            &format!("{:02}", self.selected),
+            CharPosition::new(9, 13),
+            2,
+            0,
+        );
+        draw_buffer.draw_string(stereo, CharPosition::new(12, 13), 2, 0);
+        draw_buffer.draw_string(
+            &format!("{:01$}", self.len(), Self::FRAME_DIGITS),
+            CharPosition::new(27, 13),
+            2,
+            0,
+        );
+        draw_buffer.draw_string(
+            &format!("{:01$}", self.cursor, Self::FRAME_DIGITS),
+            CharPosition::new(43, 13),
+            2,
+            0,
+        );
+        draw_buffer.draw_string(&selection, CharPosition::new(62, 13), 2, 0);
+
+        if !self.waveform_changed {
+            return;
+        }
+        self.waveform_changed = false;
+        let rect = PixelRect::from(Self::WAVEFORM_RECT);
+        let Some(sample) = &self.sample else {
+            draw_buffer.draw_rect(0, Self::WAVEFORM_RECT);
+            return;
+        };
+        draw_waveform(
+            draw_buffer,
+            rect,
+            sample,
+            self.view.clone(),
+            self.selection(),
+        );
+        if let Some(x) = frame_to_x(rect, self.view.clone(), self.cursor) {
+            draw_marker(draw_buffer, rect, x, CURSOR_COLOR);
+        }
+    }
+
+    fn draw_constant(&mut self, draw_buffer: &mut DrawBuffer) {
+        draw_buffer.draw_rect(2, CharRect::PAGE_AREA);
+        draw_buffer.draw_string("Sample", CharPosition::new(2, 13), 0, 2);
+        draw_buffer.draw_string("Length", CharPosition::new(20, 13), 0, 2);
+        draw_buffer.draw_string("Cursor", CharPosition::new(36, 13), 0, 2);
+        draw_buffer.draw_string("Selection", CharPosition::new(52, 13), 0, 2);
+        draw_buffer.draw_in_box(CharRect::new(14, 41, 1, 78), 2, 1, 3, 1);
+
+        const KEYS: [&str; 12] = [
+            "Shift-Arrows  Select",
+            "+ -           Zoom",
+            "Delete        Cut",
+            "Alt-C         Crop",
+            "Alt-Z         Silence",
+            "Alt-R         Reverse",
+            "Alt-N         Normalize",
+            "Alt-M         Amplify",
+            "Alt-I         Invert",
+            "Alt-D         Remove DC Offset",
+            "Alt-F/Alt-G   Fade In/Out",
+            "Alt-T         Mono/Stereo",
+        ];
+        for (i, text) in KEYS.into_iter().enumerate() {
+            let position = CharPosition::new(2 + (i / 6) * 38, 43 + i % 6);
+            draw_buffer.draw_string(text, position, 0, 2);
+        }
+        self.waveform_changed = true;
+    }
+
+    fn process_key_event(
+        &mut self,
+        modifiers: &winit::event::Modifiers,
+        key_event: &winit::event::KeyEvent,
+        events: &mut EventQueue<'_>,
+    ) -> PageResponse {
+        if !key_event.state.is_pressed() || self.sample.is_none() {
+            return PageResponse::None;
+        }
+
+        if modifiers.state() == ModifiersState::ALT
+            && let Key::Character(char) = &key_event.logical_key
+        {
+            return self.process_edit_key(char, events);
+        }
+
+        let select = modifiers.state() == ModifiersState::SHIFT;
+        if !select && !modifiers.state().is_empty() {
+            return PageResponse::None;
+        }
+        if key_event.logical_key == Key::Named(NamedKey::ArrowLeft) {
+            self.set_cursor(self.cursor.saturating_sub(self.cursor_step()), select);
+            return PageResponse::RequestRedraw;
+        } else if key_event.logical_key == Key::Named(NamedKey::ArrowRight) {
+            self.set_cursor(self.cursor + self.cursor_step(), select);
+            return PageResponse::RequestRedraw;
+        } else if key_event.logical_key == Key::Named(NamedKey::Home) {
+            self.set_cursor(0, select);
+            return PageResponse::RequestRedraw;
+        } else if key_event.logical_key == Key::Named(NamedKey::End) {
+            self.set_cursor(self.len(), select);
+            return PageResponse::RequestRedraw;
+        } else if key_event.logical_key == Key::Character(SmolStr::new_static("+")) {
+            self.zoom(true);
+            return PageResponse::RequestRedraw;
+        } else if key_event.logical_key == Key::Character(SmolStr::new_static("-")) {
+            self.zoom(false);
+            return PageResponse::RequestRedraw;
+        } else if key_event.logical_key == Key::Named(NamedKey::Delete) && !select {
+            self.edit(Edit::Cut, events);
+        }
+
+        PageResponse::None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Edit;
+
+    #[test]
+    fn cut_and_crop_work_on_frames() {
+        let data = [1., 2., 3., 4., 5., 6.];
+        let cut = Edit::Cut.apply(&data, true, 1..2);
+        assert_eq!(cut, Some((vec![1., 2., 5., 6.], true)));
+        let crop = Edit::Crop.apply(&data, true, 1..2);
+        assert_eq!(crop, Some((vec![3., 4.], true)));
+        // an empty sample can't be created
+        assert_eq!(Edit::Cut.apply(&data, true, 0..3), None);
+    }
+
+    #[test]
+    fn reverse_keeps_the_channels() {
+        let reversed = Edit::Reverse.apply(&[1., -1., 2., -2.], true, 0..2);
+        assert_eq!(reversed, Some((vec![2., -2., 1., -1.], true)));
+    }
+
+    #[test]
+    fn volume_edits() {
+        let data = [0.25, -0.5, 0.5];
+        let normalized = Edit::Normalize.apply(&data, false, 0..2);
+        assert_eq!(normalized, Some((vec![0.5, -1., 0.5], false)));
+        let amplified = Edit::Amplify(4.).apply(&data, false, 0..3);
+        assert_eq!(amplified, Some((vec![1., -1., 1.], false)));
+        let inverted = Edit::Invert.apply(&data, false, 1..3);
+        assert_eq!(inverted, Some((vec![0.25, 0.5, -0.5], false)));
+        let silenced = Edit::Silence.apply(&data, false, 0..1);
+        assert_eq!(silenced, Some((vec![0., -0.5, 0.5], false)));
+    }
+
+    #[test]
+    fn dc_offset_is_removed_per_channel() {
+        let removed = Edit::RemoveDcOffset.apply(&[1., 0., 3., 0.], true, 0..2);
+        assert_eq!(removed, Some((vec![-1., 0., 1., 0.], true)));
+    }
+
+    #[test]
+    fn fades() {
+        let data = [1.; 4];
+        let faded = Edit::FadeIn.apply(&data, false, 0..4);
+        assert_eq!(faded, Some((vec![0., 0.25, 0.5, 0.75], false)));
+        let faded = Edit::FadeOut.apply(&data, false, 0..4);
+        assert_eq!(faded, Some((vec![0.75, 0.5, 0.25, 0.], false)));
+    }
+
+    #[test]
+    fn toggle_stereo() {
+        let stereo = Edit::ToggleStereo.apply(&[1., 0.], false, 0..2);
+        assert_eq!(stereo, Some((vec![1., 1., 0., 0.], true)));
+        let mono = Edit::ToggleStereo.apply(&[1., 0.], true, 0..1);
+        assert_eq!(mono, Some((vec![0.5], false)));
+    }
+}
@@ -1,48 +1,289 @@
 use std::{
+    fmt::Display,
     io::{Cursor, Write},
     iter::zip,
     num::NonZero,
+    path::Path,
     str::from_utf8,
 };
 
 use torque_tracker_engine::{
+    file::impulse_format::sample::VibratoWave,
     project::{
-        note_event::Note,
+        event_command::NoteCommand,
+        note_event::{Note, NoteEvent, VolumeEffect},
         song::{Song, SongOperation},
     },
     sample::{Sample, SampleMetaData},
 };
-use winit::keyboard::{Key, NamedKey};
+use winit::keyboard::{Key, NamedKey, PhysicalKey};
 
 use crate::{
-    app::{EXECUTOR, EventQueue, GlobalEvent, SONG_OP_SEND},
-    coordinates::{CharPosition, CharRect},
+    app::{
+        EXECUTOR, EventQueue, GlobalEvent, PlaybackType, SONG_OP_SEND, send_song_msg, send_song_op,
+    },
+    coordinates::{CharPosition, CharRect, PixelRect},
     draw_buffer::DrawBuffer,
+    history::SongMsg,
     ui::{
-        header::HeaderEvent,
-        pages::{Page, PageEvent, PageResponse, pattern::PatternPageEvent},
+        dialog::error::ErrorDialog,
+        header::{Header, HeaderEvent},
+        note_keys::{NoteKeyLayout, note_from_key},
+        pages::{
+            Page, PageEvent, PageResponse, SampleEditorEvent, create_widget_list,
+            pattern::PatternPageEvent,
+        },
+        waveform::{draw_waveform, frame_count},
+        widgets::{
+            NextWidget, StandardResponse, WidgetResponse, slider::Slider, text_in::TextIn,
+            toggle::Toggle,
+        },
     },
 };
 
 #[derive(Debug, Clone)]
 pub enum SampleListEvent {
-    SetSample(u8, String, SampleMetaData),
+    /// loaded from a file
+    SetSample(u8, String, SampleMetaData, Sample),
     SelectSample(u8),
+    /// changed in the song, for example by undo. None if the sample was removed
+    SampleChanged(u8, Option<(SampleMetaData, Sample)>),
+    /// octave and layout of the pattern page
+    SetNoteKeys(u8, NoteKeyLayout),
+    // values from the slider dialogs
+    DefaultVolume(i16),
+    GlobalVolume(i16),
+    Pan(i16),
+    VibratoSpeed(i16),
+    VibratoDepth(i16),
+    VibratoRate(i16),
+    /// edited in the sample editor
+    SetSampleData(u8, Sample),
+}
+
+/// change to the metadata of the selected sample
+#[derive(Debug, Clone, Copy)]
+enum MetaChange {
+    DefaultVolume(u8),
+    GlobalVolume(u8),
+    PanEnabled(bool),
+    Pan(u8),
+    SampleRate(NonZero<u32>),
+    VibratoSpeed(u8),
+    VibratoDepth(u8),
+    VibratoRate(u8),
+    VibratoWaveform(VibratoWave),
+}
+
+// TODO: loop and sustain loop fields. The engine has no loops yet, so they would do nothing
+create_widget_list!(
+    response: Option<MetaChange>;
+    WidgetList
+    {
+        default_volume: Slider<0, 64, Option<MetaChange>>,
+        global_volume: Slider<0, 64, Option<MetaChange>>,
+        pan_enabled: Toggle<bool, Option<MetaChange>>,
+        pan: Slider<0, 64, Option<MetaChange>>,
+        sample_rate: TextIn<Option<MetaChange>>,
+        vibrato_speed: Slider<0, 64, Option<MetaChange>>,
+        vibrato_depth: Slider<0, 32, Option<MetaChange>>,
+        vibrato_rate: Slider<0, 255, Option<MetaChange>>,
+        vibrato_waveform: Toggle<VibratoWave, Option<MetaChange>>
+    }
+);
+
+/// IT defaults for a new sample
+fn default_meta(sample_rate: NonZero<u32>) -> SampleMetaData {
+    SampleMetaData {
+        default_volume: 64,
+        global_volume: 64,
+        default_pan: None,
+        vibrato_speed: 0,
+        vibrato_depth: 0,
+        vibrato_rate: 0,
+        vibrato_waveform: VibratoWave::default(),
+        sample_rate,
+        // C-5, so the sample rate is the C-5 speed
+        base_note: Note::default(),
+    }
 }
 
 pub struct SampleList {
     selected: u8,
     sample_view: u8,
-    samples: [Option<(String, SampleMetaData)>; Song::MAX_SAMPLES_INSTR],
+    samples: [Option<(String, SampleMetaData, Sample)>; Song::MAX_SAMPLES_INSTR],
+    /// names of removed samples. The song doesn't store names, so they are kept here for when the removal is undone
+    removed_names: [Option<String>; Song::MAX_SAMPLES_INSTR],
+    octave: u8,
+    note_layout: NoteKeyLayout,
+    /// key that started the note preview. Releasing it stops the note
+    live_note: Option<PhysicalKey>,
     event_proxy: winit::event_loop::EventLoopProxy<GlobalEvent>,
+    /// parameters of the selected sample
+    widgets: WidgetList,
+    /// keys go to the parameter widgets instead of the list. Switched with Tab
+    panel_focused: bool,
+    /// the waveform is only drawn again after the selection or the sample changed
+    waveform_changed: bool,
 }
 
 impl SampleList {
     const SAMPLE_VIEW_COUNT: u8 = 34;
+    /// C-5 speed shown for empty slots
+    const DEFAULT_SAMPLE_RATE: NonZero<u32> = NonZero::new(8363).unwrap();
+    const WIDGET_X: usize = 50;
+    const SLIDER_WIDTH: usize = 16;
+    const WAVEFORM_RECT: CharRect = CharRect::new(36, 47, 30, 77);
+
     pub fn new(event_proxy: winit::event_loop::EventLoopProxy<GlobalEvent>) -> Self {
+        let meta = default_meta(Self::DEFAULT_SAMPLE_RATE);
+        let default_volume = Slider::new(
+            i16::from(meta.default_volume),
+            CharPosition::new(Self::WIDGET_X, 14),
+            Self::SLIDER_WIDTH,
+            NextWidget {
+                down: Some(WidgetList::GLOBAL_VOLUME),
+                tab: Some(WidgetList::GLOBAL_VOLUME),
+                ..Default::default()
+            },
+            |n| GlobalEvent::Page(PageEvent::SampleList(SampleListEvent::DefaultVolume(n))),
+            |value| Some(MetaChange::DefaultVolume(u8::try_from(value).unwrap())),
+        );
+        let global_volume = Slider::new(
+            i16::from(meta.global_volume),
+            CharPosition::new(Self::WIDGET_X, 15),
+            Self::SLIDER_WIDTH,
+            NextWidget {
+                up: Some(WidgetList::DEFAULT_VOLUME),
+                shift_tab: Some(WidgetList::DEFAULT_VOLUME),
+                down: Some(WidgetList::PAN_ENABLED),
+                tab: Some(WidgetList::PAN_ENABLED),
+                ..Default::default()
+            },
+            |n| GlobalEvent::Page(PageEvent::SampleList(SampleListEvent::GlobalVolume(n))),
+            |value| Some(MetaChange::GlobalVolume(u8::try_from(value).unwrap())),
+        );
+        let pan_enabled = Toggle::new(
+            CharPosition::new(Self::WIDGET_X, 17),
+            Self::SLIDER_WIDTH,
+            NextWidget {
+                up: Some(WidgetList::GLOBAL_VOLUME),
+                shift_tab: Some(WidgetList::GLOBAL_VOLUME),
+                down: Some(WidgetList::PAN),
+                tab: Some(WidgetList::PAN),
+                ..Default::default()
+            },
+            &[(false, "Off"), (true, "On")],
+            |enabled| Some(MetaChange::PanEnabled(enabled)),
+        );
+        let pan = Slider::new(
+            32,
+            CharPosition::new(Self::WIDGET_X, 18),
+            Self::SLIDER_WIDTH,
+            NextWidget {
+                up: Some(WidgetList::PAN_ENABLED),
+                shift_tab: Some(WidgetList::PAN_ENABLED),
+                down: Some(WidgetList::SAMPLE_RATE),
+                tab: Some(WidgetList::SAMPLE_RATE),
+                ..Default::default()
+            },
+            |n| GlobalEvent::Page(PageEvent::SampleList(SampleListEvent::Pan(n))),
+            |value| Some(MetaChange::Pan(u8::try_from(value).unwrap())),
+        );
+        // applied on Enter or when leaving the field, so every typed digit isn't a change to the song
+        let mut sample_rate = TextIn::new(
+            CharPosition::new(Self::WIDGET_X, 20),
+            7,
+            NextWidget {
+                up: Some(WidgetList::PAN),
+                shift_tab: Some(WidgetList::PAN),
+                down: Some(WidgetList::VIBRATO_SPEED),
+                tab: Some(WidgetList::VIBRATO_SPEED),
+                ..Default::default()
+            },
+            |_| None,
+        );
+        let _ = sample_rate.set_string(meta.sample_rate.to_string());
+        let vibrato_speed = Slider::new(
+            i16::from(meta.vibrato_speed),
+            CharPosition::new(Self::WIDGET_X, 22),
+            Self::SLIDER_WIDTH,
+            NextWidget {
+                up: Some(WidgetList::SAMPLE_RATE),
+                shift_tab: Some(WidgetList::SAMPLE_RATE),
+                down: Some(WidgetList::VIBRATO_DEPTH),
+                tab: Some(WidgetList::VIBRATO_DEPTH),
+                ..Default::default()
+            },
+            |n| GlobalEvent::Page(PageEvent::SampleList(SampleListEvent::VibratoSpeed(n))),
+            |value| Some(MetaChange::VibratoSpeed(u8::try_from(value).unwrap())),
+        );
+        let vibrato_depth = Slider::new(
+            i16::from(meta.vibrato_depth),
+            CharPosition::new(Self::WIDGET_X, 23),
+            Self::SLIDER_WIDTH,
+            NextWidget {
+                up: Some(WidgetList::VIBRATO_SPEED),
+                shift_tab: Some(WidgetList::VIBRATO_SPEED),
+                down: Some(WidgetList::VIBRATO_RATE),
+                tab: Some(WidgetList::VIBRATO_RATE),
+                ..Default::default()
+            },
+            |n| GlobalEvent::Page(PageEvent::SampleList(SampleListEvent::VibratoDepth(n))),
+            |value| Some(MetaChange::VibratoDepth(u8::try_from(value).unwrap())),
+        );
+        let vibrato_rate = Slider::new(
+            i16::from(meta.vibrato_rate),
+            CharPosition::new(Self::WIDGET_X, 24),
+            Self::SLIDER_WIDTH,
+            NextWidget {
+                up: Some(WidgetList::VIBRATO_DEPTH),
+                shift_tab: Some(WidgetList::VIBRATO_DEPTH),
+                down: Some(WidgetList::VIBRATO_WAVEFORM),
+                tab: Some(WidgetList::VIBRATO_WAVEFORM),
+                ..Default::default()
+            },
+            |n| GlobalEvent::Page(PageEvent::SampleList(SampleListEvent::VibratoRate(n))),
+            |value| Some(MetaChange::VibratoRate(u8::try_from(value).unwrap())),
+        );
+        let vibrato_waveform = Toggle::new(
+            CharPosition::new(Self::WIDGET_X, 25),
+            Self::SLIDER_WIDTH,
+            NextWidget {
+                up: Some(WidgetList::VIBRATO_RATE),
+                shift_tab: Some(WidgetList::VIBRATO_RATE),
+                ..Default::default()
+            },
+            &[
+                (VibratoWave::Sine, "Sine"),
+                (VibratoWave::RampDown, "Ramp Down"),
+                (VibratoWave::Square, "Square"),
+                (VibratoWave::Random, "Random"),
+            ],
+            |wave| Some(MetaChange::VibratoWaveform(wave)),
+        );
+
         Self {
+            widgets: WidgetList {
+                selected: WidgetList::DEFAULT_VOLUME,
+                default_volume,
+                global_volume,
+                pan_enabled,
+                pan,
+                sample_rate,
+                vibrato_speed,
+                vibrato_depth,
+                vibrato_rate,
+                vibrato_waveform,
+            },
+            panel_focused: false,
+            waveform_changed: true,
             selected: 0,
             samples: [const { None }; Song::MAX_SAMPLES_INSTR],
+            removed_names: [const { None }; Song::MAX_SAMPLES_INSTR],
+            octave: Header::DEFAULT_OCTAVE,
+            note_layout: NoteKeyLayout::default(),
+            live_note: None,
             sample_view: 0,
             event_proxy,
         }
@@ -58,20 +299,192 @@ impl SampleList {
             SampleListEvent::SelectSample(s) => {
                 self.select_sample(s);
                 self.send_to_header(events);
+                self.send_to_editor(events);
                 PageResponse::RequestRedraw
             }
-            SampleListEvent::SetSample(idx, name, meta) => {
-                self.samples[usize::from(idx)] = Some((name, meta));
+            SampleListEvent::SetSample(idx, name, meta, sample) => {
+                self.samples[usize::from(idx)] = Some((name, meta, sample));
                 if self.selected == idx {
+                    self.update_widgets();
+                    self.waveform_changed = true;
                     self.send_to_header(events);
+                    self.send_to_editor(events);
+                }
+                PageResponse::RequestRedraw
+            }
+            SampleListEvent::SetNoteKeys(octave, layout) => {
+                self.octave = octave;
+                self.note_layout = layout;
+                PageResponse::None
+            }
+            SampleListEvent::SampleChanged(idx, meta) => {
+                let slot = &mut self.samples[usize::from(idx)];
+                match meta {
+                    Some((meta, sample)) => {
+                        let name = match slot.take() {
+                            Some((name, _, _)) => name,
+                            None => self.removed_names[usize::from(idx)]
+                                .take()
+                                .unwrap_or_default(),
+                        };
+                        *slot = Some((name, meta, sample));
+                    }
+                    None => {
+                        self.removed_names[usize::from(idx)] = slot.take().map(|(name, _, _)| name);
+                    }
+                }
+                if self.selected == idx {
+                    self.update_widgets();
+                    // the event doesn't say if only the metadata changed
+                    self.waveform_changed = true;
+                    self.send_to_header(events);
+                    self.send_to_editor(events);
+                }
+                PageResponse::RequestRedraw
+            }
+            SampleListEvent::DefaultVolume(n) => {
+                let change = self.widgets.default_volume.try_set(n);
+                self.dialog_change(change)
+            }
+            SampleListEvent::GlobalVolume(n) => {
+                let change = self.widgets.global_volume.try_set(n);
+                self.dialog_change(change)
+            }
+            SampleListEvent::Pan(n) => {
+                let change = self.widgets.pan.try_set(n);
+                self.dialog_change(change)
+            }
+            SampleListEvent::VibratoSpeed(n) => {
+                let change = self.widgets.vibrato_speed.try_set(n);
+                self.dialog_change(change)
+            }
+            SampleListEvent::VibratoDepth(n) => {
+                let change = self.widgets.vibrato_depth.try_set(n);
+                self.dialog_change(change)
+            }
+            SampleListEvent::VibratoRate(n) => {
+                let change = self.widgets.vibrato_rate.try_set(n);
+                self.dialog_change(change)
+            }
+            SampleListEvent::SetSampleData(idx, new_sample) => {
+                let Some((_, meta, sample)) = &mut self.samples[usize::from(idx)] else {
+                    return PageResponse::None;
+                };
+                *sample = new_sample;
+                send_song_op(SongOperation::SetSample(idx, *meta, sample.clone()));
+                if self.selected == idx {
+                    self.update_widgets();
+                    self.waveform_changed = true;
+                    self.send_to_editor(events);
+                }
+                PageResponse::RequestRedraw
+            }
+        }
+    }
+
+    fn dialog_change(&mut self, change: Result<Option<MetaChange>, ()>) -> PageResponse {
+        match change {
+            Ok(change) => {
+                if let Some(change) = change {
+                    self.change_meta(change);
                 }
                 PageResponse::RequestRedraw
             }
+            Err(_) => PageResponse::None,
+        }
+    }
+
+    /// changes the selected sample and sends it to the song. Does nothing for empty slots
+    fn change_meta(&mut self, change: MetaChange) {
+        let Some((_, meta, _)) = &mut self.samples[usize::from(self.selected)] else {
+            return;
+        };
+        match change {
+            MetaChange::DefaultVolume(vol) => meta.default_volume = vol,
+            MetaChange::GlobalVolume(vol) => meta.global_volume = vol,
+            MetaChange::PanEnabled(enabled) => {
+                meta.default_pan =
+                    enabled.then(|| u8::try_from(self.widgets.pan.get_value()).unwrap())
+            }
+            // the value is only used when the default pan is enabled
+            MetaChange::Pan(pan) => {
+                if meta.default_pan.is_none() {
+                    return;
+                }
+                meta.default_pan = Some(pan);
+            }
+            MetaChange::SampleRate(rate) => meta.sample_rate = rate,
+            MetaChange::VibratoSpeed(speed) => meta.vibrato_speed = speed,
+            MetaChange::VibratoDepth(depth) => meta.vibrato_depth = depth,
+            MetaChange::VibratoRate(rate) => meta.vibrato_rate = rate,
+            MetaChange::VibratoWaveform(wave) => meta.vibrato_waveform = wave,
+        }
+        send_song_msg(SongMsg::SampleMeta(self.selected, *meta));
+    }
+
+    /// invalid input is replaced by the current value
+    fn apply_sample_rate(&mut self) {
+        let current = self.samples[usize::from(self.selected)]
+            .as_ref()
+            .map(|(_, meta, _)| meta.sample_rate);
+        let rate = self
+            .widgets
+            .sample_rate
+            .get_str()
+            .parse()
+            .ok()
+            .and_then(NonZero::new);
+        match rate {
+            Some(rate) if Some(rate) != current => self.change_meta(MetaChange::SampleRate(rate)),
+            _ => self.update_widgets(),
         }
     }
 
+    /// shows the parameters of the selected sample without sending anything to the song
+    fn update_widgets(&mut self) {
+        let meta = match &self.samples[usize::from(self.selected)] {
+            Some((_, meta, _)) => *meta,
+            None => default_meta(Self::DEFAULT_SAMPLE_RATE),
+        };
+        let widgets = &mut self.widgets;
+        widgets
+            .default_volume
+            .try_set_silent(i16::from(meta.default_volume));
+        widgets
+            .global_volume
+            .try_set_silent(i16::from(meta.global_volume));
+        widgets.pan_enabled.set_variant(meta.default_pan.is_some());
+        if let Some(pan) = meta.default_pan {
+            widgets.pan.try_set_silent(i16::from(pan));
+        }
+        let _ = widgets.sample_rate.set_string(meta.sample_rate.to_string());
+        widgets
+            .vibrato_speed
+            .try_set_silent(i16::from(meta.vibrato_speed));
+        widgets
+            .vibrato_depth
+            .try_set_silent(i16::from(meta.vibrato_depth));
+        widgets
+            .vibrato_rate
+            .try_set_silent(i16::from(meta.vibrato_rate));
+        widgets
+            .vibrato_waveform
+            .select(|wave| wave as u8 == meta.vibrato_waveform as u8);
+    }
+
+    fn draw_waveform(&self, draw_buffer: &mut DrawBuffer) {
+        let rect = PixelRect::from(Self::WAVEFORM_RECT);
+        let Some((_, _, sample)) = &self.samples[usize::from(self.selected)] else {
+            draw_buffer.draw_rect(0, Self::WAVEFORM_RECT);
+            return;
+        };
+        draw_waveform(draw_buffer, rect, sample, 0..frame_count(sample), None);
+    }
+
     fn select_sample(&mut self, selected: u8) {
         self.selected = selected;
+        self.update_widgets();
+        self.waveform_changed = true;
         self.sample_view = if self.selected < self.sample_view {
             self.selected
         } else if self.selected > self.sample_view + Self::SAMPLE_VIEW_COUNT {
@@ -81,10 +494,16 @@ impl SampleList {
         };
     }
 
+    pub fn stop_live_note(&mut self, events: &mut EventQueue<'_>) {
+        if self.live_note.take().is_some() {
+            events.push(GlobalEvent::Playback(PlaybackType::StopLiveNote));
+        }
+    }
+
     fn send_to_header(&self, events: &mut EventQueue<'_>) {
         let name: Box<str> = self.samples[usize::from(self.selected)]
             .as_ref()
-            .map(|(n, _)| Box::from(n.as_str()))
+            .map(|(n, _, _)| Box::from(n.as_str()))
             .unwrap_or(Box::from(""));
         events.push(GlobalEvent::Header(HeaderEvent::SetSample(
             self.selected,
@@ -92,6 +511,15 @@ impl SampleList {
         )));
     }
 
+    fn send_to_editor(&self, events: &mut EventQueue<'_>) {
+        let sample = self.samples[usize::from(self.selected)]
+            .as_ref()
+            .map(|(_, _, sample)| sample.clone());
+        events.push(GlobalEvent::Page(PageEvent::SampleEditor(
+            SampleEditorEvent::SetSample(self.selected, sample),
+        )));
+    }
+
     fn send_to_pattern(&self, events: &mut EventQueue<'_>) {
         events.push(GlobalEvent::Page(PageEvent::Pattern(
             PatternPageEvent::SetSampleInstr(self.selected),
@@ -117,7 +545,7 @@ impl Page for SampleList {
                 // name
                 let name = self.samples[usize::from(n)]
                     .as_ref()
-                    .map(|(n, _)| n.as_str())
+                    .map(|(n, _, _)| n.as_str())
                     .unwrap_or("");
                 let background_color = if self.selected == n { 14 } else { 0 };
                 draw_buffer.draw_string_length(
@@ -129,10 +557,49 @@ impl Page for SampleList {
                 );
             }
         }
+
+        // parameters
+        if self.panel_focused {
+            self.widgets.draw_widgets(draw_buffer);
+        } else {
+            for widget in WidgetList::INDEX_RANGE {
+                self.widgets.get_widget(widget).draw(draw_buffer, false);
+            }
+        }
+
+        if self.waveform_changed {
+            self.draw_waveform(draw_buffer);
+            self.waveform_changed = false;
+        }
     }
 
     fn draw_constant(&mut self, draw_buffer: &mut DrawBuffer) {
         draw_buffer.draw_rect(2, CharRect::PAGE_AREA);
+
+        draw_buffer.draw_string("Default Volume", CharPosition::new(35, 14), 0, 2);
+        draw_buffer.draw_string("Global Volume", CharPosition::new(36, 15), 0, 2);
+        draw_buffer.draw_string("Default Pan", CharPosition::new(38, 17), 0, 2);
+        draw_buffer.draw_string("Pan Value", CharPosition::new(40, 18), 0, 2);
+        draw_buffer.draw_string("C-5 Speed", CharPosition::new(40, 20), 0, 2);
+        draw_buffer.draw_string("Vibrato Speed", CharPosition::new(36, 22), 0, 2);
+        draw_buffer.draw_string("Vibrato Depth", CharPosition::new(36, 23), 0, 2);
+        draw_buffer.draw_string("Vibrato Rate", CharPosition::new(37, 24), 0, 2);
+        draw_buffer.draw_string("Vibrato Waveform", CharPosition::new(33, 25), 0, 2);
+        draw_buffer.draw_in_box(CharRect::new(13, 26, 49, 67), 2, 1, 3, 1);
+
+        draw_buffer.draw_in_box(
+            CharRect::new(
+                Self::WAVEFORM_RECT.top() - 1,
+                Self::WAVEFORM_RECT.bot() + 1,
+                Self::WAVEFORM_RECT.left() - 1,
+                Self::WAVEFORM_RECT.right() + 1,
+            ),
+            2,
+            1,
+            3,
+            1,
+        );
+        self.waveform_changed = true;
     }
 
     fn process_key_event(
@@ -142,13 +609,57 @@ impl Page for SampleList {
         events: &mut EventQueue<'_>,
     ) -> PageResponse {
         if !key_event.state.is_pressed() {
+            if self.live_note == Some(key_event.physical_key) {
+                self.live_note = None;
+                events.push(GlobalEvent::Playback(PlaybackType::StopLiveNote));
+            }
             return PageResponse::None;
         }
 
+        if key_event.logical_key == Key::Named(NamedKey::Tab) && !self.panel_focused {
+            if modifiers.state().is_empty() {
+                self.panel_focused = true;
+                return PageResponse::RequestRedraw;
+            }
+            return PageResponse::None;
+        }
+
+        if self.panel_focused {
+            let WidgetResponse { standard, extra } =
+                self.widgets.process_input(key_event, modifiers, events);
+            if let Some(change) = extra.flatten() {
+                self.change_meta(change);
+            }
+            return match standard {
+                StandardResponse::SwitchFocus(next) => {
+                    if self.widgets.selected == WidgetList::SAMPLE_RATE {
+                        self.apply_sample_rate();
+                    }
+                    self.widgets.selected = next;
+                    PageResponse::RequestRedraw
+                }
+                StandardResponse::RequestRedraw => PageResponse::RequestRedraw,
+                // Tab past the first or last widget goes back to the list
+                StandardResponse::None if key_event.logical_key == Key::Named(NamedKey::Tab) => {
+                    self.panel_focused = false;
+                    PageResponse::RequestRedraw
+                }
+                StandardResponse::None
+                    if key_event.logical_key == Key::Named(NamedKey::Enter)
+                        && self.widgets.selected == WidgetList::SAMPLE_RATE =>
+                {
+                    self.apply_sample_rate();
+                    PageResponse::RequestRedraw
+                }
+                StandardResponse::None => PageResponse::None,
+            };
+        }
+
         if key_event.logical_key == Key::Named(NamedKey::ArrowUp) && modifiers.state().is_empty() {
             if let Some(s) = self.selected.checked_sub(1) {
                 self.select_sample(s);
                 self.send_to_header(events);
+                self.send_to_editor(events);
                 self.send_to_pattern(events);
                 return PageResponse::RequestRedraw;
             }
@@ -158,6 +669,7 @@ impl Page for SampleList {
             if self.selected + 1 < 100 {
                 self.select_sample(self.selected + 1);
                 self.send_to_header(events);
+                self.send_to_editor(events);
                 self.send_to_pattern(events);
                 return PageResponse::RequestRedraw;
             }
@@ -177,190 +689,207 @@ impl Page for SampleList {
                         return;
                     };
                     let file_name = file.file_name();
-                    // HOW TO SYMPHONIA: https://github.com/pdeljanov/Symphonia/blob/master/symphonia/examples/basic-interleaved.rs
                     // IO is not async as symphonia doesn't support async IO.
                     // This is fine as i have two background threads and don't
                     // do IO that often.
-                    let Ok(file) = std::fs::File::open(file.path()) else {
-                        eprintln!("error opening file");
-                        return;
-                    };
-                    let mss = symphonia::core::io::MediaSourceStream::new(
-                        Box::new(file),
-                        Default::default(),
-                    );
-                    let probe = symphonia::default::get_probe();
-                    let Ok(probed) = probe.format(
-                        // TODO: add file extension to the hint
-                        &symphonia::core::probe::Hint::new(),
-                        mss,
-                        &Default::default(),
-                        &Default::default(),
-                    ) else {
-                        eprintln!("format error");
-                        return;
-                    };
-                    let mut format = probed.format;
-                    let Some(track) = format
-                        .tracks()
-                        .iter()
-                        .find(|t| t.codec_params.codec != symphonia::core::codecs::CODEC_TYPE_NULL)
-                    else {
-                        eprintln!("no decodable track found");
-                        return;
-                    };
-                    let Ok(mut decoder) = symphonia::default::get_codecs()
-                        .make(&track.codec_params, &Default::default())
-                    else {
-                        eprintln!("no decoder found");
-                        return;
-                    };
-                    let track_id = track.id;
-                    let Some(sample_rate) = track.codec_params.sample_rate else {
-                        eprintln!("no sample rate");
-                        return;
-                    };
-                    let Some(sample_rate) = NonZero::new(sample_rate) else {
-                        eprintln!("sample rate = 0");
-                        return;
-                    };
-                    let mut buf = Vec::new();
-                    // i don't know yet. after the first iteration of the loop this is set
-                    let mut stereo: Option<bool> = None;
-                    loop {
-                        let packet = format.next_packet();
-                        let packet = match packet {
-                            Ok(p) => p,
-                            // this is used as a end of stream signal. don't ask me why
-                            Err(symphonia::core::errors::Error::IoError(e))
-                                if e.kind() == std::io::ErrorKind::UnexpectedEof =>
-                            {
-                                break;
-                            }
-                            Err(e) => {
-                                eprintln!("decoding error: {e:?}");
-                                return;
-                            }
-                        };
-
-                        if packet.track_id() != track_id {
-                            continue;
-                        }
-                        match decoder.decode(&packet) {
-                            Ok(audio_buf) => {
-                                fn append_to_buf<T>(
-                                    buf: &mut Vec<f32>,
-                                    in_buf: &symphonia::core::audio::AudioBuffer<T>,
-                                    stereo: &mut Option<bool>,
-                                ) where
-                                    T: symphonia::core::sample::Sample,
-                                    f32: symphonia::core::conv::FromSample<T>,
-                                {
-                                    use symphonia::core::{
-                                        audio::{Channels, Signal},
-                                        conv::FromSample,
-                                    };
-                                    if in_buf
-                                        .spec()
-                                        .channels
-                                        .contains(Channels::FRONT_LEFT | Channels::FRONT_RIGHT)
-                                    {
-                                        // stereo + plus maybe other channels that i ignore
-                                        assert!(stereo.is_none() || *stereo == Some(true));
-                                        *stereo = Some(true);
-                                        let left = in_buf.chan(0);
-                                        let right = in_buf.chan(1);
-                                        assert!(left.len() == right.len());
-                                        let iter = zip(left, right).flat_map(|(l, r)| {
-                                            [f32::from_sample(*l), f32::from_sample(*r)]
-                                        });
-                                        buf.extend(iter);
-                                    } else if in_buf.spec().channels.contains(Channels::FRONT_LEFT)
-                                    {
-                                        // assert not
-                                        assert!(stereo.is_none() || *stereo == Some(false));
-                                        *stereo = Some(false);
-                                        buf.extend(
-                                            in_buf
-                                                .chan(0)
-                                                .iter()
-                                                .map(|sample| f32::from_sample(*sample)),
-                                        );
-                                    } else {
-                                        eprintln!("no usable channel in sample data")
-                                    }
-                                }
-                                use symphonia::core::audio::AudioBufferRef;
-                                match audio_buf {
-                                    AudioBufferRef::U8(d) => {
-                                        append_to_buf(&mut buf, &d, &mut stereo)
-                                    }
-                                    AudioBufferRef::U16(d) => {
-                                        append_to_buf(&mut buf, &d, &mut stereo)
-                                    }
-                                    AudioBufferRef::U24(d) => {
-                                        append_to_buf(&mut buf, &d, &mut stereo)
-                                    }
-                                    AudioBufferRef::U32(d) => {
-                                        append_to_buf(&mut buf, &d, &mut stereo)
-                                    }
-                                    AudioBufferRef::S8(d) => {
-                                        append_to_buf(&mut buf, &d, &mut stereo)
-                                    }
-                                    AudioBufferRef::S16(d) => {
-                                        append_to_buf(&mut buf, &d, &mut stereo)
-                                    }
-                                    AudioBufferRef::S24(d) => {
-                                        append_to_buf(&mut buf, &d, &mut stereo)
-                                    }
-                                    AudioBufferRef::S32(d) => {
-                                        append_to_buf(&mut buf, &d, &mut stereo)
-                                    }
-                                    AudioBufferRef::F32(d) => {
-                                        append_to_buf(&mut buf, &d, &mut stereo)
-                                    }
-                                    AudioBufferRef::F64(d) => {
-                                        append_to_buf(&mut buf, &d, &mut stereo)
-                                    }
-                                }
-                            }
-                            Err(symphonia::core::errors::Error::DecodeError(_)) => (),
-                            Err(_) => break,
+                    let (meta, sample) = match load_sample(file.path()) {
+                        Ok(loaded) => loaded,
+                        Err(err) => {
+                            let message = format!("{file_name}: {err}");
+                            proxy
+                                .send_event(GlobalEvent::OpenDialog(Box::new(move || {
+                                    Box::new(ErrorDialog::new(message))
+                                })))
+                                .unwrap();
+                            return;
                         }
-                    }
-                    // hopefully both of these compile to a memcopy...
-                    let sample = if stereo.unwrap() {
-                        Sample::new_stereo_interpolated(buf)
-                    } else {
-                        Sample::new_mono(buf)
-                    };
-                    // TODO: get the real metadata / sane defaults / configurable
-                    let meta = SampleMetaData {
-                        default_volume: 32,
-                        global_volume: 32,
-                        default_pan: None,
-                        vibrato_speed: 0,
-                        vibrato_depth: 0,
-                        vibrato_rate: 0,
-                        vibrato_waveform: Default::default(),
-                        sample_rate,
-                        base_note: Note::new(64).unwrap(),
                     };
                     // send to UI
                     proxy
                         .send_event(GlobalEvent::Page(PageEvent::SampleList(
-                            SampleListEvent::SetSample(idx, file_name, meta),
+                            SampleListEvent::SetSample(idx, file_name, meta, sample.clone()),
                         )))
                         .unwrap();
                     drop(proxy);
                     // send to playback
                     let operation = SongOperation::SetSample(idx, meta, sample);
-                    SONG_OP_SEND.get().unwrap().send(operation).await.unwrap();
+                    SONG_OP_SEND
+                        .get()
+                        .unwrap()
+                        .send(SongMsg::Op(operation))
+                        .await
+                        .unwrap();
                 })
                 .detach();
+        } else if modifiers.state().is_empty()
+            && !key_event.repeat
+            && self.samples[usize::from(self.selected)].is_some()
+            && let Some(note) = note_from_key(key_event, self.note_layout, self.octave)
+        {
+            // preview the selected sample
+            self.live_note = Some(key_event.physical_key);
+            events.push(GlobalEvent::Playback(PlaybackType::LiveNote(NoteEvent {
+                note,
+                sample_instr: self.selected,
+                vol: VolumeEffect::None,
+                command: NoteCommand::None,
+            })));
         }
         // TODO: add PageUp and PageDown
 
         PageResponse::None
     }
 }
+
+#[derive(Debug)]
+enum SampleLoadError {
+    Open(std::io::Error),
+    UnsupportedFormat,
+    NoTrack,
+    NoDecoder,
+    NoSampleRate,
+    Decode(symphonia::core::errors::Error),
+    /// the file switched between mono and stereo in the middle
+    ChannelsChanged,
+    NoUsableChannel,
+    Empty,
+}
+
+impl Display for SampleLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SampleLoadError::Open(err) => write!(f, "can't open the file: {err}"),
+            SampleLoadError::UnsupportedFormat => write!(f, "unsupported file format"),
+            SampleLoadError::NoTrack => write!(f, "no audio track found"),
+            SampleLoadError::NoDecoder => write!(f, "unsupported codec"),
+            SampleLoadError::NoSampleRate => write!(f, "no valid sample rate"),
+            SampleLoadError::Decode(err) => write!(f, "decoding error: {err}"),
+            SampleLoadError::ChannelsChanged => write!(f, "number of channels changed"),
+            SampleLoadError::NoUsableChannel => write!(f, "no usable channel"),
+            SampleLoadError::Empty => write!(f, "the file contains no audio"),
+        }
+    }
+}
+
+// HOW TO SYMPHONIA: https://github.com/pdeljanov/Symphonia/blob/master/symphonia/examples/basic-interleaved.rs
+// TODO: read the loop points once the engine has loops. symphonia doesn't read them from the smpl
+// chunk of WAV files
+fn load_sample(path: &Path) -> Result<(SampleMetaData, Sample), SampleLoadError> {
+    let file = std::fs::File::open(path).map_err(SampleLoadError::Open)?;
+    let mss = symphonia::core::io::MediaSourceStream::new(Box::new(file), Default::default());
+    let probe = symphonia::default::get_probe();
+    let probed = probe
+        .format(
+            // TODO: add file extension to the hint
+            &symphonia::core::probe::Hint::new(),
+            mss,
+            &Default::default(),
+            &Default::default(),
+        )
+        .map_err(|_| SampleLoadError::UnsupportedFormat)?;
+    let mut format = probed.format;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != symphonia::core::codecs::CODEC_TYPE_NULL)
+        .ok_or(SampleLoadError::NoTrack)?;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &Default::default())
+        .map_err(|_| SampleLoadError::NoDecoder)?;
+    let track_id = track.id;
+    let sample_rate = track
+        .codec_params
+        .sample_rate
+        .and_then(NonZero::new)
+        .ok_or(SampleLoadError::NoSampleRate)?;
+    let mut buf = Vec::new();
+    // i don't know yet. after the first iteration of the loop this is set
+    let mut stereo: Option<bool> = None;
+    loop {
+        let packet = match format.next_packet() {
+            Ok(p) => p,
+            // this is used as a end of stream signal. don't ask me why
+            Err(symphonia::core::errors::Error::IoError(e))
+                if e.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                break;
+            }
+            Err(e) => return Err(SampleLoadError::Decode(e)),
+        };
+
+        if packet.track_id() != track_id {
+            continue;
+        }
+        match decoder.decode(&packet) {
+            Ok(audio_buf) => {
+                fn append_to_buf<T>(
+                    buf: &mut Vec<f32>,
+                    in_buf: &symphonia::core::audio::AudioBuffer<T>,
+                    stereo: &mut Option<bool>,
+                ) -> Result<(), SampleLoadError>
+                where
+                    T: symphonia::core::sample::Sample,
+                    f32: symphonia::core::conv::FromSample<T>,
+                {
+                    use symphonia::core::{
+                        audio::{Channels, Signal},
+                        conv::FromSample,
+                    };
+                    let channels = in_buf.spec().channels;
+                    // stereo + plus maybe other channels that i ignore
+                    let is_stereo =
+                        if channels.contains(Channels::FRONT_LEFT | Channels::FRONT_RIGHT) {
+                            true
+                        } else if channels.contains(Channels::FRONT_LEFT) {
+                            false
+                        } else {
+                            return Err(SampleLoadError::NoUsableChannel);
+                        };
+                    if *stereo.get_or_insert(is_stereo) != is_stereo {
+                        return Err(SampleLoadError::ChannelsChanged);
+                    }
+                    if is_stereo {
+                        let left = in_buf.chan(0);
+                        let right = in_buf.chan(1);
+                        let iter = zip(left, right)
+                            .flat_map(|(l, r)| [f32::from_sample(*l), f32::from_sample(*r)]);
+                        buf.extend(iter);
+                    } else {
+                        buf.extend(
+                            in_buf
+                                .chan(0)
+                                .iter()
+                                .map(|sample| f32::from_sample(*sample)),
+                        );
+                    }
+                    Ok(())
+                }
+                use symphonia::core::audio::AudioBufferRef;
+                match audio_buf {
+                    AudioBufferRef::U8(d) => append_to_buf(&mut buf, &d, &mut stereo),
+                    AudioBufferRef::U16(d) => append_to_buf(&mut buf, &d, &mut stereo),
+                    AudioBufferRef::U24(d) => append_to_buf(&mut buf, &d, &mut stereo),
+                    AudioBufferRef::U32(d) => append_to_buf(&mut buf, &d, &mut stereo),
+                    AudioBufferRef::S8(d) => append_to_buf(&mut buf, &d, &mut stereo),
+                    AudioBufferRef::S16(d) => append_to_buf(&mut buf, &d, &mut stereo),
+                    AudioBufferRef::S24(d) => append_to_buf(&mut buf, &d, &mut stereo),
+                    AudioBufferRef::S32(d) => append_to_buf(&mut buf, &d, &mut stereo),
+                    AudioBufferRef::F32(d) => append_to_buf(&mut buf, &d, &mut stereo),
+                    AudioBufferRef::F64(d) => append_to_buf(&mut buf, &d, &mut stereo),
+                }?;
+            }
+            Err(symphonia::core::errors::Error::DecodeError(_)) => (),
+            Err(_) => break,
+        }
+    }
+    let Some(stereo) = stereo.filter(|_| !buf.is_empty()) else {
+        return Err(SampleLoadError::Empty);
+    };
+    // hopefully both of these compile to a memcopy...
+    let sample = if stereo {
+        Sample::new_stereo_interpolated(buf)
+    } else {
+        Sample::new_mono(buf)
+    };
+    Ok((default_meta(sample_rate), sample))
+}
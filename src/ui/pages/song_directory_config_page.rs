@@ -35,6 +35,10 @@ pub enum SDCChange {
     InitialTempo(i16),
     InitialSpeed(i16),
     GlobalVolume(i16),
+    /// changed in the song, for example by undo. These don't send the change back to the song
+    SongTempo(u8),
+    SongSpeed(u8),
+    SongGlobalVolume(u8),
     // MixingVolume(i16),
     // Seperation(i16),
 }
@@ -169,6 +173,27 @@ impl SongDirectoryConfigPage {
                 Ok(_) => PageResponse::RequestRedraw,
                 Err(_) => PageResponse::None,
             },
+            SDCChange::SongTempo(n) => {
+                if self.widgets.initial_tempo.try_set_silent(i16::from(n)) {
+                    PageResponse::RequestRedraw
+                } else {
+                    PageResponse::None
+                }
+            }
+            SDCChange::SongSpeed(n) => {
+                if self.widgets.initial_speed.try_set_silent(i16::from(n)) {
+                    PageResponse::RequestRedraw
+                } else {
+                    PageResponse::None
+                }
+            }
+            SDCChange::SongGlobalVolume(n) => {
+                if self.widgets.global_volume.try_set_silent(i16::from(n)) {
+                    PageResponse::RequestRedraw
+                } else {
+                    PageResponse::None
+                }
+            }
             SDCChange::InitialTempo(n) => match self.widgets.initial_tempo.try_set(n) {
                 Ok(_) => PageResponse::RequestRedraw,
                 Err(_) => PageResponse::None,
@@ -0,0 +1,83 @@
+use std::ops::Range;
+
+use torque_tracker_engine::sample::Sample;
+
+use crate::{coordinates::PixelRect, draw_buffer::DrawBuffer};
+
+pub const BACKGROUND_COLOR: u8 = 0;
+pub const WAVE_COLOR: u8 = 13;
+pub const SELECTION_COLOR: u8 = 1;
+
+/// frames of the sample without the padding the engine puts on both ends
+pub fn frame_count(sample: &Sample) -> usize {
+    sample.len_with_pad() - 2 * Sample::PAD_SIZE_EACH
+}
+
+/// left and right value of the frame, counted without the padding. Mono samples have the same value in both
+pub fn frame(sample: &Sample, idx: usize) -> [f32; 2] {
+    sample.index(idx + Sample::PAD_SIZE_EACH).to_sample()
+}
+
+/// clears rect and draws the frames of the sample into it. Stereo samples get the upper half for the
+/// left channel and the lower half for the right one. The selected frames get a different background.
+/// The frames are counted without the padding
+pub fn draw_waveform(
+    draw_buffer: &mut DrawBuffer,
+    rect: PixelRect,
+    sample: &Sample,
+    frames: Range<usize>,
+    selection: Option<Range<usize>>,
+) {
+    draw_buffer.draw_pixel_rect(BACKGROUND_COLOR, rect);
+    if frames.is_empty() {
+        return;
+    }
+
+    let channels = if sample.is_mono() { 1 } else { 2 };
+    let width = rect.horizontal_range().count();
+    let channel_height = rect.vertical_range().count() / channels;
+    // leaves a free pixel above and below
+    let half_height = (channel_height / 2 - 1) as f32;
+
+    for channel in 0..channels {
+        let middle = rect.top() + channel * channel_height + channel_height / 2;
+        let to_y = |value: f32| (middle as f32 - value.clamp(-1., 1.) * half_height) as usize;
+        for x in 0..width {
+            // when zoomed in far there are more pixels than frames, so every pixel gets at least one
+            let start = frames.start + x * frames.len() / width;
+            let end = (frames.start + (x + 1) * frames.len() / width).max(start + 1);
+            // only once, so the left channel isn't painted over
+            if channel == 0
+                && let Some(selection) = &selection
+                && start < selection.end
+                && selection.start < end
+            {
+                draw_marker(draw_buffer, rect, rect.left() + x, SELECTION_COLOR);
+            }
+            let (min, max) = (start..end)
+                .map(|idx| frame(sample, idx)[channel])
+                .fold((f32::MAX, f32::MIN), |(min, max), v| {
+                    (min.min(v), max.max(v))
+                });
+            draw_buffer.draw_pixel_rect(
+                WAVE_COLOR,
+                PixelRect::new(to_y(max), to_y(min), rect.left() + x, rect.left() + x),
+            );
+        }
+    }
+}
+
+/// x position of the frame in rect. None if the frame isn't shown.
+/// The frame after the last one is on the right edge, so a cursor at the end can be drawn
+pub fn frame_to_x(rect: PixelRect, frames: Range<usize>, frame: usize) -> Option<usize> {
+    if frame < frames.start || frame > frames.end || frames.is_empty() {
+        return None;
+    }
+    let width = rect.horizontal_range().count();
+    Some(rect.left() + (frame - frames.start) * (width - 1) / frames.len())
+}
+
+/// vertical line over the whole height of rect
+pub fn draw_marker(draw_buffer: &mut DrawBuffer, rect: PixelRect, x: usize, color: u8) {
+    draw_buffer.draw_pixel_rect(color, PixelRect::new(rect.top(), rect.bot(), x, x));
+}
@@ -278,9 +278,19 @@ impl<const MIN: i16, const MAX: i16, R> Slider<MIN, MAX, R> {
         }
     }
 
+    pub fn get_value(&self) -> i16 {
+        *self.number
+    }
+
     pub fn try_set(&mut self, value: i16) -> Result<R, ()> {
         self.number.try_set(value).map(|_| (self.callback)(value))
     }
+
+    /// doesn't call the callback. For values that were already changed somewhere else.
+    /// false if the value is out of range
+    pub fn try_set_silent(&mut self, value: i16) -> bool {
+        self.number.try_set(value).is_ok()
+    }
 }
 
 impl<const MIN: i16, const MAX: i16, R> Debug for Slider<MIN, MAX, R> {
@@ -88,4 +88,20 @@ impl<T: Copy + 'static, R> Toggle<T, R> {
     pub fn get_variant(&self) -> T {
         self.variants[self.state].0
     }
+
+    /// doesn't call the callback. Does nothing if the variant isn't one of the options
+    pub fn set_variant(&mut self, variant: T)
+    where
+        T: PartialEq,
+    {
+        self.select(|v| v == variant);
+    }
+
+    /// selects the first variant that matches. For variants without PartialEq.
+    /// doesn't call the callback
+    pub fn select(&mut self, matches: impl Fn(T) -> bool) {
+        if let Some(state) = self.variants.iter().position(|(v, _)| matches(*v)) {
+            self.state = state;
+        }
+    }
 }